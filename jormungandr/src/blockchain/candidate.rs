@@ -0,0 +1,124 @@
+use crate::blockcfg::{ChainLength, Header, HeaderHash};
+
+use std::collections::{HashMap, HashSet};
+
+/// A possible new tip found in a batch of headers received from the network.
+#[allow(dead_code)]
+#[derive(Clone, Debug)]
+pub struct TipCandidate {
+    /// The header at the end of the chain.
+    pub header: Header,
+    /// The parent of the first header of the chain in the batch.
+    /// This block has to be known to the node for the candidate
+    /// to be considered for the fork choice.
+    pub anchor: HeaderHash,
+    /// The number of headers in the batch that make up the chain,
+    /// from the anchor to the candidate header.
+    pub headers_count: usize,
+}
+
+#[allow(dead_code)]
+impl TipCandidate {
+    pub fn hash(&self) -> HeaderHash {
+        self.header.hash()
+    }
+
+    pub fn chain_length(&self) -> ChainLength {
+        self.header.chain_length()
+    }
+}
+
+/// Groups the headers into chains following the parent links, and
+/// returns the header at the end of each chain as a tip candidate.
+///
+/// Chains that branch within the batch give one candidate per branch.
+/// Headers that do not link to the rest of the batch start chains of
+/// their own, anchored at their parents. The candidates are returned in
+/// the order their headers appear in the batch.
+///
+/// Not used until the header batches pulled from peers are handled.
+#[allow(dead_code)]
+pub fn candidates_from_headers(headers: &[Header]) -> Vec<TipCandidate> {
+    let parents: HashMap<HeaderHash, HeaderHash> = headers
+        .iter()
+        .map(|header| (header.hash(), header.parent_id()))
+        .collect();
+    let has_children: HashSet<HeaderHash> = parents.values().cloned().collect();
+
+    let mut seen = HashSet::new();
+    headers
+        .iter()
+        .filter(|header| {
+            let hash = header.hash();
+            // Skip duplicates in the batch as well as inner headers.
+            seen.insert(hash) && !has_children.contains(&hash)
+        })
+        .map(|header| {
+            let mut anchor = header.parent_id();
+            let mut headers_count = 1;
+            // The walk back is bounded by the batch size in case
+            // the batch contains a cycle of parent links.
+            while let Some(parent) = parents.get(&anchor) {
+                if headers_count == parents.len() {
+                    break;
+                }
+                anchor = parent.clone();
+                headers_count += 1;
+            }
+            TipCandidate {
+                header: header.clone(),
+                anchor,
+                headers_count,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockcfg::BlockDate;
+    use crate::blockchain::test_utils;
+
+    #[test]
+    fn candidates_of_branching_batch() {
+        let genesis = test_utils::genesis_block();
+        let trunk = test_utils::headers(&test_utils::chain(&genesis.header, 3));
+        let long_branch = test_utils::headers(&test_utils::chain(&trunk[2], 3));
+        // The branches are dated apart from the long chain,
+        // so that their headers differ.
+        let date = |slot_id| BlockDate { epoch: 0, slot_id };
+        let short_branch = test_utils::child_block(&trunk[0], date(20)).header;
+        // Not linked to the rest of the batch.
+        let detached_parent = test_utils::child_block(&long_branch[0], date(30)).header;
+        let detached = test_utils::child_block(&detached_parent, date(31)).header;
+
+        let mut batch = Vec::new();
+        batch.extend(trunk.iter().cloned());
+        batch.extend(long_branch.iter().cloned());
+        batch.push(short_branch.clone());
+        batch.push(detached.clone());
+        // A duplicate does not give another candidate.
+        batch.push(short_branch.clone());
+
+        let candidates = candidates_from_headers(&batch);
+        let summary: Vec<_> = candidates
+            .iter()
+            .map(|c| (c.hash(), c.anchor, c.headers_count))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (long_branch[2].hash(), genesis.header.hash(), 6),
+                (short_branch.hash(), genesis.header.hash(), 2),
+                (detached.hash(), detached_parent.hash(), 1),
+            ]
+        );
+        assert_eq!(candidates[0].chain_length(), long_branch[2].chain_length());
+    }
+
+    #[test]
+    fn no_candidates_in_empty_batch() {
+        assert!(candidates_from_headers(&[]).is_empty());
+    }
+}
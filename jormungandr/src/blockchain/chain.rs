@@ -123,6 +123,8 @@ pub struct Blockchain {
     ledgers: Multiverse<Arc<Ledger>>,
 
     storage: Storage,
+
+    max_reorg_depth: Option<u32>,
 }
 
 pub enum PreCheckedHeader {
@@ -171,15 +173,26 @@ pub struct PostCheckedHeader {
 }
 
 impl Blockchain {
-    pub fn new(storage: NodeStorage, ref_cache_ttl: Duration) -> Self {
+    pub fn new(
+        storage: NodeStorage,
+        ref_cache_ttl: Duration,
+        max_reorg_depth: Option<u32>,
+    ) -> Self {
         Blockchain {
             branches: Branches::new(),
             ref_cache: RefCache::new(ref_cache_ttl),
             ledgers: Multiverse::new(),
             storage: Storage::new(storage),
+            max_reorg_depth,
         }
     }
 
+    /// The maximum number of blocks that the node is allowed to roll back
+    /// from its tip to switch to another branch, or `None` for no limit.
+    pub fn max_reorg_depth(&self) -> Option<u32> {
+        self.max_reorg_depth
+    }
+
     pub fn storage(&self) -> &Storage {
         &self.storage
     }
@@ -1,4 +1,9 @@
+use crate::blockcfg::{ChainLength, HeaderHash};
 use crate::blockchain::{Ref, Storage};
+use chain_storage::error::Error as StorageError;
+use futures::future::Either;
+use slog::Logger;
+use tokio::prelude::*;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Hash)]
 pub enum ComparisonResult {
@@ -13,24 +18,111 @@ pub enum ComparisonResult {
 /// on the same block date is to a requirement to call this function as it will still
 /// work to make a choice as to which of these two Ref is the right choice.
 ///
-pub fn compare_against(storage: &Storage, current: &Ref, candidate: &Ref) -> ComparisonResult {
-    let rollback_possible = check_rollback_up_to(
-        // missing max depth parameter from the protocol settings
-        storage, current, candidate,
+/// The longer chain is preferred. If both chains have the same length, the
+/// Ref with the lowest block hash wins, so that all nodes seeing the same
+/// two tips settle on the same one regardless of the order they received
+/// them in.
+///
+/// If `max_reorg_depth` is set, the candidate is refused when switching to
+/// it would roll back more than that number of blocks from the current tip.
+///
+pub fn compare_against(
+    storage: &Storage,
+    current: &Ref,
+    candidate: &Ref,
+    max_reorg_depth: Option<u32>,
+    logger: Logger,
+) -> impl Future<Item = ComparisonResult, Error = StorageError> {
+    let prefer_candidate = is_better_tip(
+        (current.chain_length(), &current.hash()),
+        (candidate.chain_length(), &candidate.hash()),
     );
 
-    if rollback_possible && current.chain_length() < candidate.chain_length() {
-        ComparisonResult::PreferCandidate
-    } else {
-        ComparisonResult::PreferCurrent
+    if !prefer_candidate {
+        return Either::A(future::ok(ComparisonResult::PreferCurrent));
     }
+    let max_depth = match max_reorg_depth {
+        None => return Either::A(future::ok(ComparisonResult::PreferCandidate)),
+        Some(max_depth) => max_depth,
+    };
+
+    let candidate_hash = candidate.hash();
+    Either::B(
+        storage
+            .rollback_depth(current.hash(), candidate_hash, max_depth.into())
+            .map(move |depth| match depth {
+                Some(_) => ComparisonResult::PreferCandidate,
+                None => {
+                    error!(
+                        logger,
+                        "refusing to switch to a chain that forks off deeper than the maximum reorg depth";
+                        "candidate" => %candidate_hash,
+                        "max_reorg_depth" => max_depth,
+                    );
+                    ComparisonResult::PreferCurrent
+                }
+            }),
+    )
 }
 
-fn check_rollback_up_to(
-    // TODO: missing max depth parameter
-    _: &Storage,
-    _ref1: &Ref,
-    _ref2: &Ref,
+// Checks if the candidate tip, given by its chain length and hash, is
+// to be preferred over the current one by the rules of `compare_against`.
+fn is_better_tip(
+    current: (ChainLength, &HeaderHash),
+    candidate: (ChainLength, &HeaderHash),
 ) -> bool {
-    true
+    let (current_length, current_hash) = current;
+    let (candidate_length, candidate_hash) = candidate;
+    current_length < candidate_length
+        || (current_length == candidate_length && candidate_hash < current_hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockcfg::{BlockDate, Header};
+    use crate::blockchain::test_utils;
+
+    fn winner<'a>(current: &'a Header, candidate: &'a Header) -> &'a Header {
+        if is_better_tip(
+            (current.chain_length(), &current.hash()),
+            (candidate.chain_length(), &candidate.hash()),
+        ) {
+            candidate
+        } else {
+            current
+        }
+    }
+
+    #[test]
+    fn equal_length_tips_are_chosen_regardless_of_order() {
+        let genesis = test_utils::genesis_block();
+        let date = |slot_id| BlockDate { epoch: 0, slot_id };
+        let first = test_utils::child_block(&genesis.header, date(1)).header;
+        let second = test_utils::child_block(&genesis.header, date(2)).header;
+        assert_eq!(first.chain_length(), second.chain_length());
+        assert_ne!(first.hash(), second.hash());
+
+        let expected = if first.hash() < second.hash() {
+            &first
+        } else {
+            &second
+        };
+        assert_eq!(winner(&first, &second).hash(), expected.hash());
+        assert_eq!(winner(&second, &first).hash(), expected.hash());
+        // The current tip is kept against itself.
+        assert!(!is_better_tip(
+            (first.chain_length(), &first.hash()),
+            (first.chain_length(), &first.hash())
+        ));
+    }
+
+    #[test]
+    fn longer_chain_is_preferred() {
+        let genesis = test_utils::genesis_block();
+        let blocks = test_utils::chain(&genesis.header, 2);
+        let (shorter, longer) = (&blocks[0].header, &blocks[1].header);
+        assert_eq!(winner(shorter, longer).hash(), longer.hash());
+        assert_eq!(winner(longer, shorter).hash(), longer.hash());
+    }
 }
@@ -0,0 +1,131 @@
+use crate::blockcfg::{Header, HeaderHash};
+use thiserror::Error;
+
+// Nothing verifies the header chains pulled from peers yet,
+// as `BlockMsg::ChainHeaders` is not processed.
+#[allow(dead_code)]
+#[derive(Error, Debug)]
+pub enum ChainVerifyError {
+    #[error("header {hash} has parent {parent}, expected {expected_parent}")]
+    ParentMismatch {
+        hash: HeaderHash,
+        parent: HeaderHash,
+        expected_parent: HeaderHash,
+    },
+    #[error("header {hash} has chain length {chain_length}, expected {expected_chain_length}")]
+    ChainLengthMismatch {
+        hash: HeaderHash,
+        chain_length: u32,
+        expected_chain_length: u32,
+    },
+    #[error("header {hash} is at the maximum chain length and cannot have children")]
+    ChainLengthOverflow { hash: HeaderHash },
+}
+
+/// Checks that a batch of headers received from a peer forms a chain
+/// extending the anchor, a header already known to this node.
+///
+/// The first header in the batch must be a child of the anchor, and every
+/// following header must be a child of the one before it, with the chain
+/// length increasing by one at each step. An empty batch is trivially
+/// continuous.
+#[allow(dead_code)]
+pub fn verify_batch_from_anchor(anchor: &Header, batch: &[Header]) -> Result<(), ChainVerifyError> {
+    let mut parent_hash = anchor.hash();
+    let mut parent_chain_length = u32::from(anchor.chain_length());
+    for header in batch {
+        let hash = header.hash();
+        let parent = header.parent_id();
+        if parent != parent_hash {
+            return Err(ChainVerifyError::ParentMismatch {
+                hash,
+                parent,
+                expected_parent: parent_hash,
+            });
+        }
+        let chain_length = u32::from(header.chain_length());
+        let expected_chain_length = parent_chain_length
+            .checked_add(1)
+            .ok_or(ChainVerifyError::ChainLengthOverflow { hash: parent_hash })?;
+        if chain_length != expected_chain_length {
+            return Err(ChainVerifyError::ChainLengthMismatch {
+                hash,
+                chain_length,
+                expected_chain_length,
+            });
+        }
+        parent_hash = hash;
+        parent_chain_length = chain_length;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockcfg::{BlockDate, ChainLength};
+    use crate::blockchain::test_utils;
+
+    #[test]
+    fn batch_continuing_anchor() {
+        let genesis = test_utils::genesis_block();
+        let blocks = test_utils::chain(&genesis.header, 4);
+        let headers = test_utils::headers(&blocks);
+        verify_batch_from_anchor(&genesis.header, &headers).unwrap();
+        verify_batch_from_anchor(&headers[1], &headers[2..]).unwrap();
+        verify_batch_from_anchor(&headers[3], &[]).unwrap();
+    }
+
+    #[test]
+    fn batch_not_continuing_anchor() {
+        let genesis = test_utils::genesis_block();
+        let headers = test_utils::headers(&test_utils::chain(&genesis.header, 4));
+
+        // The batch does not start at a child of the anchor.
+        match verify_batch_from_anchor(&genesis.header, &headers[1..]) {
+            Err(ChainVerifyError::ParentMismatch { hash, .. }) => {
+                assert_eq!(hash, headers[1].hash())
+            }
+            res => panic!("unexpected result: {:?}", res),
+        }
+        // A header is missing in the middle of the batch.
+        let gapped = vec![headers[0].clone(), headers[2].clone()];
+        match verify_batch_from_anchor(&genesis.header, &gapped) {
+            Err(ChainVerifyError::ParentMismatch { hash, .. }) => {
+                assert_eq!(hash, headers[2].hash())
+            }
+            res => panic!("unexpected result: {:?}", res),
+        }
+        // A child of the anchor with the wrong chain length.
+        let date = BlockDate {
+            epoch: 0,
+            slot_id: 1,
+        };
+        let header =
+            test_utils::build_block(&genesis.header.hash(), ChainLength::from(2), date).header;
+        match verify_batch_from_anchor(&genesis.header, &[header]) {
+            Err(ChainVerifyError::ChainLengthMismatch {
+                chain_length,
+                expected_chain_length,
+                ..
+            }) => assert_eq!((chain_length, expected_chain_length), (2, 1)),
+            res => panic!("unexpected result: {:?}", res),
+        }
+    }
+
+    #[test]
+    fn chain_length_overflow() {
+        let date = |slot_id| BlockDate { epoch: 0, slot_id };
+        let anchor = test_utils::build_block(
+            &HeaderHash::zero_hash(),
+            ChainLength::from(u32::max_value()),
+            date(0),
+        )
+        .header;
+        let child = test_utils::build_block(&anchor.hash(), ChainLength::from(0), date(1)).header;
+        match verify_batch_from_anchor(&anchor, &[child]) {
+            Err(ChainVerifyError::ChainLengthOverflow { hash }) => assert_eq!(hash, anchor.hash()),
+            res => panic!("unexpected result: {:?}", res),
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use crate::blockcfg::{Epoch, Header};
+
+use std::collections::BTreeMap;
+
+/// Returns the last header of each epoch found in the given headers,
+/// i.e. the header with the greatest chain length within the epoch.
+///
+/// The headers are expected to be from a single chain, but they do not
+/// need to be in order. Epochs without any headers are not present in
+/// the returned map.
+///
+/// There is no caller until the epoch boundaries are looked up
+/// in the pulled header chains.
+#[allow(dead_code)]
+pub fn epoch_last_headers<'a, I>(headers: I) -> BTreeMap<Epoch, Header>
+where
+    I: IntoIterator<Item = &'a Header>,
+{
+    let mut last_headers: BTreeMap<Epoch, Header> = BTreeMap::new();
+    for header in headers {
+        let epoch = header.block_date().epoch;
+        let is_later = last_headers
+            .get(&epoch)
+            .map_or(true, |last| header.chain_length() > last.chain_length());
+        if is_later {
+            last_headers.insert(epoch, header.clone());
+        }
+    }
+    last_headers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockcfg::BlockDate;
+    use crate::blockchain::test_utils;
+
+    #[test]
+    fn last_headers_of_epochs() {
+        let genesis = test_utils::genesis_block();
+        let date = |epoch: Epoch, slot_id| BlockDate { epoch, slot_id };
+        // Epochs 2 and 4 have no blocks.
+        let dates = vec![date(0, 5), date(1, 0), date(1, 7), date(3, 2), date(5, 1)];
+        let mut headers = vec![genesis.header.clone()];
+        for date in dates {
+            let header = test_utils::child_block(headers.last().unwrap(), date).header;
+            headers.push(header);
+        }
+
+        // The order of the headers does not matter.
+        let mut shuffled = headers.clone();
+        shuffled.reverse();
+        shuffled.swap(1, 3);
+
+        let last_headers = epoch_last_headers(&shuffled);
+        let summary: Vec<_> = last_headers
+            .iter()
+            .map(|(&epoch, header)| (epoch, header.hash()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (0, headers[1].hash()),
+                (1, headers[3].hash()),
+                (3, headers[4].hash()),
+                (5, headers[5].hash()),
+            ]
+        );
+        assert!(!last_headers.contains_key(&2));
+        assert!(!last_headers.contains_key(&4));
+    }
+
+    #[test]
+    fn no_headers() {
+        assert!(epoch_last_headers(&[]).is_empty());
+    }
+}
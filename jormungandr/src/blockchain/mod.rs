@@ -1,7 +1,10 @@
 mod branch;
+mod candidate;
 mod chain;
 mod chain_selection;
 mod checkpoints;
+mod continuity;
+mod epochs;
 mod multiverse;
 mod process;
 mod reference;
@@ -9,11 +12,17 @@ mod reference_cache;
 mod storage;
 mod tip;
 
+#[cfg(test)]
+pub mod test_utils;
+
 pub use self::{
     branch::{Branch, Branches},
+    candidate::{candidates_from_headers, TipCandidate},
     chain::{Blockchain, Error, ErrorKind, PreCheckedHeader, MAIN_BRANCH_TAG},
     chain_selection::{compare_against, ComparisonResult},
     checkpoints::Checkpoints,
+    continuity::{verify_batch_from_anchor, ChainVerifyError},
+    epochs::epoch_last_headers,
     multiverse::Multiverse,
     process::{handle_input, process_new_ref, Error as ProcessError},
     reference::Ref,
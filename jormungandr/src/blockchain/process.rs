@@ -230,15 +230,28 @@ pub fn process_new_ref(
 
     tip.clone()
         .get_ref()
+        .map_err(|_: std::convert::Infallible| unreachable!())
         .and_then(move |tip_ref| {
             if tip_ref.hash() == candidate.block_parent_hash() {
                 info!(logger, "update current branch tip");
-                A(A(tip.update_ref(candidate).map(|_| true)))
+                A(tip
+                    .update_ref(candidate)
+                    .map(|_| true)
+                    .map_err(|_: std::convert::Infallible| unreachable!()))
             } else {
-                match compare_against(blockchain.storage(), &tip_ref, &candidate) {
+                let max_reorg_depth = blockchain.max_reorg_depth();
+                B(compare_against(
+                    blockchain.storage(),
+                    &tip_ref,
+                    &candidate,
+                    max_reorg_depth,
+                    logger.clone(),
+                )
+                .map_err(|e| Error::with_chain(e, "Cannot compare the candidate branch"))
+                .and_then(move |comparison| match comparison {
                     ComparisonResult::PreferCurrent => {
                         info!(logger, "create new branch");
-                        A(B(future::ok(false)))
+                        A(future::ok(false))
                     }
                     ComparisonResult::PreferCandidate => {
                         info!(logger, "switching to new candidate branch");
@@ -246,12 +259,12 @@ pub fn process_new_ref(
                             .branches_mut()
                             .apply_or_create(candidate)
                             .and_then(move |branch| tip.swap(branch))
-                            .map(|()| true))
+                            .map(|()| true)
+                            .map_err(|_: std::convert::Infallible| unreachable!()))
                     }
-                }
+                }))
             }
         })
-        .map_err(|_: std::convert::Infallible| unreachable!())
         .and_then(move |tip_updated| {
             if tip_updated {
                 A(storage
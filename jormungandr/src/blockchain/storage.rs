@@ -151,6 +151,36 @@ impl Storage {
         })
     }
 
+    /// Returns the number of blocks to roll back from `tip` to reach
+    /// the closest block that is also an ancestor of `other`, or `None`
+    /// if that takes more than `max_depth` blocks.
+    pub fn rollback_depth(
+        &self,
+        tip: HeaderHash,
+        other: HeaderHash,
+        max_depth: u64,
+    ) -> impl Future<Item = Option<u64>, Error = StorageError> {
+        let mut inner = self.inner.clone();
+        future::poll_fn(move || Ok(inner.poll_lock())).and_then(move |store| {
+            let mut tip_info = store.get_block_info(&tip)?;
+            let mut other_info = store.get_block_info(&other)?;
+            let tip_depth = tip_info.depth;
+            while other_info.depth > tip_info.depth {
+                other_info = store.get_block_info(&other_info.parent_id())?;
+            }
+            while tip_info.block_hash != other_info.block_hash {
+                if tip_depth - tip_info.depth == max_depth {
+                    return Ok(None);
+                }
+                if tip_info.depth == other_info.depth {
+                    other_info = store.get_block_info(&other_info.parent_id())?;
+                }
+                tip_info = store.get_block_info(&tip_info.parent_id())?;
+            }
+            Ok(Some(tip_depth - tip_info.depth))
+        })
+    }
+
     pub fn get_checkpoints(
         &self,
         tip: HeaderHash,
@@ -208,3 +238,57 @@ impl Stream for BlockStream {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockcfg::BlockDate;
+    use crate::blockchain::test_utils;
+
+    // Builds a fork of `count` blocks from the given parent, dated in
+    // the given epoch so that the fork differs from the main chain.
+    fn fork(parent: &Block, epoch: u32, count: usize) -> Vec<Block> {
+        let date = BlockDate { epoch, slot_id: 0 };
+        let start = test_utils::child_block(&parent.header, date);
+        let rest = test_utils::chain(&start.header, count - 1);
+        Some(start).into_iter().chain(rest).collect()
+    }
+
+    #[test]
+    fn rollback_depth_is_limited() {
+        let genesis = test_utils::genesis_block();
+        let main = test_utils::chain(&genesis.header, 5);
+        let deep_fork = fork(&main[1], 1, 3);
+        let shallow_fork = fork(&main[3], 2, 2);
+        let storage = test_utils::memory_storage(
+            Some(&genesis)
+                .into_iter()
+                .chain(&main)
+                .chain(&deep_fork)
+                .chain(&shallow_fork),
+        );
+        let tip = main[4].header.hash();
+        let rollback_depth = |other: &Block, max_depth| {
+            storage
+                .rollback_depth(tip, other.header.hash(), max_depth)
+                .wait()
+                .unwrap()
+        };
+
+        // The deep fork branches off three blocks below the tip.
+        assert_eq!(rollback_depth(&deep_fork[2], 3), Some(3));
+        assert_eq!(rollback_depth(&deep_fork[2], 2), None);
+        assert_eq!(rollback_depth(&deep_fork[0], 2), None);
+
+        // The shallow fork branches off one block below the tip,
+        // even if it is longer than the main chain.
+        assert_eq!(rollback_depth(&shallow_fork[0], 1), Some(1));
+        assert_eq!(rollback_depth(&shallow_fork[1], 1), Some(1));
+        assert_eq!(rollback_depth(&shallow_fork[1], 0), None);
+
+        // Blocks of the main chain are reached by rolling back to them.
+        assert_eq!(rollback_depth(&main[4], 0), Some(0));
+        assert_eq!(rollback_depth(&main[2], 2), Some(2));
+        assert_eq!(rollback_depth(&main[2], 1), None);
+    }
+}
@@ -0,0 +1,71 @@
+//! Helpers to build blocks and storages for the tests.
+
+use crate::blockcfg::{
+    Block, BlockDate, BlockVersion, ChainLength, ContentsBuilder, Header, HeaderBuilderNew,
+    HeaderHash,
+};
+use crate::blockchain::Storage;
+use chain_storage::{memory::MemoryBlockStore, store::BlockStore};
+
+/// Builds an empty block with the given parent, chain length and date.
+pub fn build_block(parent_id: &HeaderHash, chain_length: ChainLength, date: BlockDate) -> Block {
+    let contents = ContentsBuilder::new().into();
+    let header = HeaderBuilderNew::new(BlockVersion::Genesis, &contents)
+        .set_parent(parent_id, chain_length)
+        .set_date(date)
+        .to_unsigned_header()
+        .unwrap()
+        .generalize();
+    Block { header, contents }
+}
+
+/// Builds an empty root block, at the first slot of epoch 0.
+pub fn genesis_block() -> Block {
+    let date = BlockDate {
+        epoch: 0,
+        slot_id: 0,
+    };
+    build_block(&HeaderHash::zero_hash(), ChainLength::from(0), date)
+}
+
+/// Builds an empty block on top of the given parent, at the given date.
+pub fn child_block(parent: &Header, date: BlockDate) -> Block {
+    build_block(&parent.hash(), parent.chain_length().increase(), date)
+}
+
+/// Builds a chain of `count` empty blocks on top of the given parent,
+/// one in each slot following the parent's slot.
+pub fn chain(parent: &Header, count: usize) -> Vec<Block> {
+    let mut blocks: Vec<Block> = Vec::with_capacity(count);
+    for _ in 0..count {
+        let block = {
+            let parent = blocks.last().map_or(parent, |block| &block.header);
+            let date = parent.block_date();
+            let date = BlockDate {
+                epoch: date.epoch,
+                slot_id: date.slot_id + 1,
+            };
+            child_block(parent, date)
+        };
+        blocks.push(block);
+    }
+    blocks
+}
+
+/// Returns the headers of the given blocks.
+pub fn headers(blocks: &[Block]) -> Vec<Header> {
+    blocks.iter().map(|block| block.header.clone()).collect()
+}
+
+/// Creates an in-memory storage with the given blocks, which are to be
+/// listed after their parents.
+pub fn memory_storage<'a, I>(blocks: I) -> Storage
+where
+    I: IntoIterator<Item = &'a Block>,
+{
+    let mut store = MemoryBlockStore::new();
+    for block in blocks {
+        store.put_block(block).unwrap();
+    }
+    Storage::new(Box::new(store))
+}
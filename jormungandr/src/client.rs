@@ -62,34 +62,37 @@ fn handle_get_block_tip(blockchain_tip: &Tip) -> Result<Header, Error> {
 
 const MAX_HEADERS: usize = 2000;
 
-fn find_latest_checkpoint(
-    checkpoints: &[HeaderHash],
+/// Finds the most recent block among the given locators that is present
+/// in the storage. Locators that are not known to this node are skipped,
+/// so the locator list can be a sparse selection of the peer's chain.
+/// Returns `None` if no locator is known.
+fn find_intersection(
+    locators: &[HeaderHash],
     storage: &Storage,
-    block0_hash: &HeaderHash,
-) -> Result<HeaderHash, Error> {
-    // Filter out the checkpoints that don't exist in the storage;
-    // among the checkpoints present, find the latest by chain length.
-    let mut latest_checkpoint = None;
-    for hash in checkpoints {
+) -> Result<Option<HeaderHash>, Error> {
+    let mut intersection = None;
+    for hash in locators {
         match storage.get_with_info(hash.clone()).wait() {
-            Ok(Some((_, info))) => match latest_checkpoint {
-                None => {
-                    latest_checkpoint = Some((info.depth, hash));
-                }
-                Some((latest_depth, _)) => {
-                    if info.depth > latest_depth {
-                        latest_checkpoint = Some((info.depth, hash));
-                    }
+            Ok(Some((_, info))) => match intersection {
+                Some((latest_depth, _)) if info.depth <= latest_depth => {}
+                _ => {
+                    intersection = Some((info.depth, hash));
                 }
             },
             Ok(None) => continue,
             Err(e) => return Err(e.into()),
         }
     }
-    match latest_checkpoint {
-        Some((_, hash)) => Ok(hash.clone()),
-        None => Ok(block0_hash.clone()),
-    }
+    Ok(intersection.map(|(_, hash)| hash.clone()))
+}
+
+fn find_latest_checkpoint(
+    checkpoints: &[HeaderHash],
+    storage: &Storage,
+    block0_hash: &HeaderHash,
+) -> Result<HeaderHash, Error> {
+    let intersection = find_intersection(checkpoints, storage)?;
+    Ok(intersection.unwrap_or_else(|| block0_hash.clone()))
 }
 
 fn handle_get_headers_range(
@@ -203,3 +206,49 @@ fn handle_pull_blocks_to_tip(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockcfg::BlockDate;
+    use crate::blockchain::test_utils;
+
+    #[test]
+    fn intersection_of_locators() {
+        let genesis = test_utils::genesis_block();
+        let blocks = test_utils::chain(&genesis.header, 5);
+        // The fork is dated in the next epoch to make it differ from the chain.
+        let fork_date = BlockDate {
+            epoch: 1,
+            slot_id: 0,
+        };
+        let fork_start = test_utils::child_block(&blocks[1].header, fork_date);
+        let fork_rest = test_utils::chain(&fork_start.header, 2);
+        let fork: Vec<Block> = Some(fork_start).into_iter().chain(fork_rest).collect();
+        let storage = test_utils::memory_storage(Some(&genesis).into_iter().chain(&blocks));
+        let hash = |block: &Block| block.header.hash();
+
+        // The most recent known locator is found regardless of the order,
+        // skipping the unknown ones.
+        let locators = vec![
+            hash(&fork[2]),
+            hash(&fork[0]),
+            hash(&blocks[1]),
+            hash(&genesis),
+        ];
+        assert_eq!(
+            find_intersection(&locators, &storage).unwrap(),
+            Some(hash(&blocks[1]))
+        );
+        let locators = vec![hash(&genesis), hash(&blocks[3]), hash(&blocks[2])];
+        assert_eq!(
+            find_intersection(&locators, &storage).unwrap(),
+            Some(hash(&blocks[3]))
+        );
+
+        // None of the locators is known.
+        let locators: Vec<_> = fork.iter().map(hash).collect();
+        assert_eq!(find_intersection(&locators, &storage).unwrap(), None);
+        assert_eq!(find_intersection(&[], &storage).unwrap(), None);
+    }
+}
@@ -104,6 +104,8 @@ pub struct BootstrappedNode {
     logger: Logger,
     explorer_db: Option<explorer::ExplorerDB>,
     rest_context: Option<rest::Context>,
+    network_events: network::events::EventBus,
+    stats_counter: StatsCounter,
 }
 
 const FRAGMENT_TASK_QUEUE_LEN: usize = 1024;
@@ -128,7 +130,7 @@ fn start_services(bootstrapped_node: BootstrappedNode) -> Result<(), start_up::E
     let leadership_garbage_collection_interval =
         bootstrapped_node.settings.leadership.log_ttl.into();
 
-    let stats_counter = StatsCounter::default();
+    let stats_counter = bootstrapped_node.stats_counter;
 
     let (fragment_pool, pool_logs) = {
         let stats_counter = stats_counter.clone();
@@ -215,6 +217,8 @@ fn start_services(bootstrapped_node: BootstrappedNode) -> Result<(), start_up::E
         let block_msgbox = block_task.clone();
         let block0_hash = bootstrapped_node.block0_hash;
         let config = bootstrapped_node.settings.network.clone();
+        let network_events = bootstrapped_node.network_events.clone();
+        let stats_counter = stats_counter.clone();
         let channels = network::Channels {
             client_box: client_msgbox,
             transaction_box: fragment_msgbox,
@@ -227,6 +231,8 @@ fn start_services(bootstrapped_node: BootstrappedNode) -> Result<(), start_up::E
                 block0_hash,
                 input: network_queue,
                 channels,
+                events: network_events,
+                stats_counter,
             };
             network::start(info, params)
                 // FIXME: more graceful error reporting
@@ -346,12 +352,17 @@ fn bootstrap(initialized_node: InitializedNode) -> Result<BootstrappedNode, star
         storage,
         new_epoch_announcements.clone(),
         block_cache_ttl,
+        settings.chain_selection.max_reorg_depth,
     )?;
 
+    let stats_counter = StatsCounter::default();
+    let network_events = network::events::EventBus::new(stats_counter.clone(), logger.clone());
+
     let bootstrapped = network::bootstrap(
         &settings.network,
         blockchain.clone(),
         blockchain_tip.clone(),
+        &network_events,
         &bootstrap_logger,
     )?;
 
@@ -381,6 +392,8 @@ fn bootstrap(initialized_node: InitializedNode) -> Result<BootstrappedNode, star
         logger,
         explorer_db,
         rest_context,
+        network_events,
+        stats_counter,
     })
 }
 
@@ -1,7 +1,7 @@
 use super::{grpc, BlockConfig};
-use crate::blockcfg::{Block, HeaderHash};
+use crate::blockcfg::{Block, ChainLength, HeaderHash};
 use crate::blockchain::{self, Blockchain, Error as BlockchainError, PreCheckedHeader, Ref, Tip};
-use crate::settings::start::network::Peer;
+use crate::settings::start::network::{Configuration, Peer};
 use chain_core::property::HasHeader;
 use network_core::client::{BlockService, Client as _};
 use network_core::error::Error as NetworkError;
@@ -10,10 +10,11 @@ use slog::Logger;
 use thiserror::Error;
 use tokio::prelude::*;
 use tokio::runtime::Runtime;
+use tokio::timer::{self, timeout};
 
-use std::fmt::Debug;
 use std::io;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 #[derive(Error, Debug)]
 pub enum Error {
@@ -27,6 +28,12 @@ pub enum Error {
     PullRequestFailed { source: NetworkError },
     #[error("bootstrap pull stream failed")]
     PullStreamFailed { source: NetworkError },
+    #[error("bootstrap timed out")]
+    Timeout,
+    #[error("bootstrap peer stopped sending blocks")]
+    Stalled,
+    #[error("timer failed")]
+    TimerFailed { source: timer::Error },
     #[error("block header check failed")]
     HeaderCheckFailed { source: BlockchainError },
     #[error("received block {0} is already present")]
@@ -41,6 +48,7 @@ pub enum Error {
 
 pub fn bootstrap_from_peer(
     peer: Peer,
+    config: &Configuration,
     blockchain: Blockchain,
     branch: Tip,
     logger: Logger,
@@ -52,7 +60,10 @@ pub fn bootstrap_from_peer(
     let blockchain2 = blockchain.clone();
     let logger2 = logger.clone();
 
-    let bootstrap = grpc::connect(peer.address(), None, runtime.executor())
+    let stall_timeout = config.bootstrap_stall_timeout;
+    let progress_interval = config.bootstrap_progress_interval;
+
+    let bootstrap = grpc::connect(peer.address(), None, &config.tcp, runtime.executor())
         .map_err(|e| Error::Connect { source: e })
         .and_then(|client: Connection<BlockConfig>| {
             client
@@ -61,12 +72,38 @@ pub fn bootstrap_from_peer(
         })
         .join(branch.get_ref().map_err(|_| unreachable!()))
         .and_then(move |(mut client, tip)| {
-            let tip_hash = tip.hash();
-            debug!(logger, "pulling blocks starting from {}", tip_hash);
+            // The tip of the peer is only used to estimate the progress,
+            // so failing to get it is not an error.
             client
-                .pull_blocks_to_tip(&[tip_hash])
-                .map_err(|e| Error::PullRequestFailed { source: e })
-                .and_then(move |stream| bootstrap_from_stream(blockchain, tip, stream, logger))
+                .tip()
+                .then(|res| Ok::<_, Error>(res.ok()))
+                .and_then(move |peer_tip| {
+                    client
+                        .ready()
+                        .map_err(|e| Error::ClientNotReady { source: e })
+                        .map(move |client| (client, peer_tip))
+                })
+                .and_then(move |(mut client, peer_tip)| {
+                    let tip_hash = tip.hash();
+                    debug!(logger, "pulling blocks starting from {}", tip_hash);
+                    let progress = Progress::new(
+                        peer_tip.map(|header| header.chain_length()),
+                        progress_interval,
+                        &logger,
+                    );
+                    client
+                        .pull_blocks_to_tip(&[tip_hash])
+                        .map_err(|e| Error::PullRequestFailed { source: e })
+                        .and_then(move |stream| {
+                            bootstrap_from_stream(
+                                blockchain,
+                                tip,
+                                with_stall_timeout(stream, stall_timeout),
+                                progress,
+                                logger,
+                            )
+                        })
+                })
         })
         .and_then(move |tip| {
             blockchain::process_new_ref(logger2, blockchain2, branch, tip.clone())
@@ -74,27 +111,135 @@ pub fn bootstrap_from_peer(
                 .map(|()| tip)
         });
 
-    runtime.block_on_all(bootstrap)
+    match config.bootstrap_timeout {
+        Some(timeout) => runtime.block_on_all(
+            bootstrap
+                .timeout(timeout)
+                .map_err(|e| from_timeout(e, Error::Timeout, |e| e)),
+        ),
+        None => runtime.block_on_all(bootstrap),
+    }
+}
+
+// Converts a timeout error, returning `elapsed` if the time has run out.
+fn from_timeout<E, F>(e: timeout::Error<E>, elapsed: Error, map_inner: F) -> Error
+where
+    F: FnOnce(E) -> Error,
+{
+    if e.is_elapsed() {
+        elapsed
+    } else if e.is_timer() {
+        Error::TimerFailed {
+            source: e.into_timer().unwrap(),
+        }
+    } else {
+        map_inner(e.into_inner().unwrap())
+    }
+}
+
+/// Fails the block stream with `Error::Stalled` if no block is received
+/// within `stall_timeout`.
+fn with_stall_timeout<S>(
+    stream: S,
+    stall_timeout: Duration,
+) -> impl Stream<Item = Block, Error = Error>
+where
+    S: Stream<Item = Block, Error = NetworkError>,
+{
+    stream
+        .timeout(stall_timeout)
+        .map_err(|e| from_timeout(e, Error::Stalled, |e| Error::PullStreamFailed { source: e }))
 }
 
 fn bootstrap_from_stream<S>(
     blockchain: Blockchain,
     tip: Arc<Ref>,
     stream: S,
+    progress: Progress,
     logger: Logger,
 ) -> impl Future<Item = Arc<Ref>, Error = Error>
 where
-    S: Stream<Item = Block, Error = NetworkError>,
-    S::Error: Debug,
+    S: Stream<Item = Block, Error = Error>,
 {
     let fold_logger = logger.clone();
     stream
-        .map_err(|e| Error::PullStreamFailed { source: e })
-        .fold(tip, move |_, block| {
-            handle_block(blockchain.clone(), block, fold_logger.clone())
+        .fold((tip, progress), move |(_, mut progress), block| {
+            handle_block(blockchain.clone(), block, fold_logger.clone()).map(move |tip| {
+                if progress.block_applied(Instant::now()) {
+                    progress.report(&tip);
+                }
+                (tip, progress)
+            })
+        })
+        .map(|(tip, progress)| {
+            progress.report(&tip);
+            tip
         })
 }
 
+/// Tracks the blocks applied during the bootstrap and periodically
+/// reports the progress in the log.
+struct Progress {
+    blocks: u64,
+    peer_chain_length: Option<ChainLength>,
+    interval: Duration,
+    started: Instant,
+    last_report: Instant,
+    logger: Logger,
+}
+
+impl Progress {
+    fn new(peer_chain_length: Option<ChainLength>, interval: Duration, logger: &Logger) -> Self {
+        let now = Instant::now();
+        Progress {
+            blocks: 0,
+            peer_chain_length,
+            interval,
+            started: now,
+            last_report: now,
+            logger: logger.clone(),
+        }
+    }
+
+    // Counts a block applied at the given time. Returns true if
+    // the progress is due to be reported.
+    fn block_applied(&mut self, now: Instant) -> bool {
+        self.blocks += 1;
+        if now.duration_since(self.last_report) >= self.interval {
+            self.last_report = now;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn report(&self, tip: &Ref) {
+        let chain_length = u32::from(tip.chain_length());
+        let elapsed = self.started.elapsed().as_secs();
+        match self.peer_chain_length.map(u32::from) {
+            Some(peer_chain_length) => {
+                let remaining = peer_chain_length.saturating_sub(chain_length);
+                info!(
+                    self.logger,
+                    "bootstrap progress: {} blocks applied", self.blocks;
+                    "chain_length" => chain_length,
+                    "peer_chain_length" => peer_chain_length,
+                    "remaining" => remaining,
+                    "elapsed_secs" => elapsed,
+                );
+            }
+            None => {
+                info!(
+                    self.logger,
+                    "bootstrap progress: {} blocks applied", self.blocks;
+                    "chain_length" => chain_length,
+                    "elapsed_secs" => elapsed,
+                );
+            }
+        }
+    }
+}
+
 fn handle_block(
     mut blockchain: Blockchain,
     block: Block,
@@ -130,3 +275,70 @@ fn handle_block(
                 .map_err(|e| Error::ApplyBlockFailed { source: e })
         })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::bootstrap_from_peers;
+    use futures::stream;
+    use std::net::SocketAddr;
+
+    // Pulls blocks from a peer that has accepted the request,
+    // but never sends anything.
+    fn pull_from_silent_peer() -> Result<(), Error> {
+        let silent =
+            stream::poll_fn(|| -> Poll<Option<Block>, NetworkError> { Ok(Async::NotReady) });
+        let mut runtime = Runtime::new().unwrap();
+        runtime
+            .block_on(with_stall_timeout(silent, Duration::from_millis(10)).collect())
+            .map(|_| ())
+    }
+
+    #[test]
+    fn silent_stream_stalls() {
+        match pull_from_silent_peer() {
+            Err(Error::Stalled) => {}
+            Err(e) => panic!("unexpected error: {:?}", e),
+            Ok(_) => panic!("the stream should have stalled"),
+        }
+    }
+
+    #[test]
+    fn progress_is_due_at_interval() {
+        let logger = Logger::root(slog::Discard, o!());
+        let interval = Duration::from_secs(10);
+        let mut progress = Progress::new(None, interval, &logger);
+        let start = progress.started;
+        // Apply a block every second, over a minute.
+        let due: Vec<u64> = (1..=60)
+            .filter(|&secs| progress.block_applied(start + Duration::from_secs(secs)))
+            .collect();
+        assert_eq!(due, vec![10, 20, 30, 40, 50, 60]);
+        assert_eq!(progress.blocks, 60);
+
+        // A gap in the stream delays the next report.
+        assert!(progress.block_applied(start + Duration::from_secs(95)));
+        assert!(!progress.block_applied(start + Duration::from_secs(100)));
+        assert!(progress.block_applied(start + Duration::from_secs(105)));
+    }
+
+    #[test]
+    fn next_peer_is_tried_after_stall() {
+        let logger = Logger::root(slog::Discard, o!());
+        let addresses: Vec<SocketAddr> = vec![
+            "127.0.0.1:3001".parse().unwrap(),
+            "127.0.0.1:3002".parse().unwrap(),
+        ];
+        let mut tried = Vec::new();
+        let bootstrapped = bootstrap_from_peers(addresses.clone(), &logger, |address, _| {
+            tried.push(address);
+            if tried.len() == 1 {
+                pull_from_silent_peer()
+            } else {
+                Ok(())
+            }
+        });
+        assert_eq!(bootstrapped, Some(addresses[1]));
+        assert_eq!(tried, addresses);
+    }
+}
@@ -34,7 +34,12 @@ pub fn connect(
         channels,
         logger: state.logger,
     });
-    let cf = grpc::connect(addr, Some(node_id), state.global.executor.clone());
+    let cf = grpc::connect(
+        addr,
+        Some(node_id),
+        &state.global.config.tcp,
+        state.global.executor.clone(),
+    );
     let handle = ConnectHandle { receiver };
     let future = ConnectFuture {
         sender: Some(sender),
@@ -1,4 +1,5 @@
 mod connect;
+mod request_limit;
 
 use super::{
     buffer_sizes,
@@ -24,6 +25,7 @@ use futures::prelude::*;
 use slog::Logger;
 
 pub use self::connect::{connect, ConnectError, ConnectFuture, ConnectHandle};
+use self::request_limit::RequestLimit;
 
 #[must_use = "Client must be polled"]
 pub struct Client<S>
@@ -36,6 +38,7 @@ where
     inbound: InboundSubscriptions<S>,
     block_solicitations: OutboundSubscription<Vec<HeaderHash>>,
     chain_pulls: OutboundSubscription<ChainPullRequest<HeaderHash>>,
+    request_limit: RequestLimit,
     block_sink: BlockAnnouncementProcessor,
     fragment_sink: FragmentProcessor,
     gossip_processor: GossipProcessor,
@@ -100,6 +103,7 @@ where
             global_state.clone(),
             logger.new(o!("stream" => "gossip", "direction" => "in")),
         );
+        let request_limit = RequestLimit::new(global_state.config.max_inflight_requests_per_peer);
 
         Client {
             service: inner,
@@ -108,6 +112,7 @@ where
             inbound,
             block_solicitations: comms.subscribe_to_block_solicitations(),
             chain_pulls: comms.subscribe_to_chain_pulls(),
+            request_limit,
             block_sink,
             fragment_sink,
             gossip_processor,
@@ -312,10 +317,7 @@ where
             buffer_sizes::CHAIN_PULL,
             logger.clone(),
         );
-        // TODO: make sure that back pressure on the number of requests
-        // in flight, imposed through self.service.poll_ready(),
-        // prevents unlimited spawning of these tasks.
-        // https://github.com/input-output-hk/jormungandr/issues/1034
+        let request_guard = self.request_limit.start_request();
         self.global_state.spawn(
             block_box
                 .send(BlockMsg::ChainHeaders(handle))
@@ -348,6 +350,10 @@ where
                             );
                         })
                         .map(|_| ())
+                })
+                .then(move |res| {
+                    drop(request_guard);
+                    res
                 }),
         );
     }
@@ -366,6 +372,7 @@ where
         let block_box = self.block_sink.message_box();
         let logger = self.logger.clone();
         let err_logger = logger.clone();
+        let request_guard = self.request_limit.start_request();
         self.global_state.spawn(
             self.service
                 .pull_blocks_to_tip(&req.from)
@@ -393,6 +400,10 @@ where
                     .map(move |_| {
                         debug!(logger, "PullBlocksToTip response processed");
                     })
+                })
+                .then(move |res| {
+                    drop(request_guard);
+                    res
                 }),
         );
     }
@@ -409,6 +420,7 @@ where
         let block_box = self.block_sink.message_box();
         let logger = self.logger.clone();
         let err_logger = logger.clone();
+        let request_guard = self.request_limit.start_request();
         self.global_state.spawn(
             self.service
                 .get_blocks(block_ids)
@@ -439,6 +451,10 @@ where
                     .map(move |_| {
                         debug!(logger, "GetBlocks response processed");
                     })
+                })
+                .then(move |res| {
+                    drop(request_guard);
+                    res
                 }),
         );
     }
@@ -574,23 +590,25 @@ where
             // Block solicitations and chain pulls are special:
             // they are handled with client requests on the client side,
             // but on the server side, they are fed into the block event stream.
-            progress.update(self.block_solicitations.poll().unwrap().map(|maybe_item| {
-                match maybe_item {
-                    Some(block_ids) => {
-                        self.solicit_blocks(&block_ids);
-                        Continue
-                    }
-                    None => {
-                        debug!(self.logger, "outbound block solicitation stream closed");
-                        Disconnect
+            // The number of these requests in flight is limited per peer,
+            // further requests are left queued in the outbound streams.
+            if let Async::Ready(()) = self.request_limit.poll_ready() {
+                progress.update(self.block_solicitations.poll().unwrap().map(|maybe_item| {
+                    match maybe_item {
+                        Some(block_ids) => {
+                            self.solicit_blocks(&block_ids);
+                            Continue
+                        }
+                        None => {
+                            debug!(self.logger, "outbound block solicitation stream closed");
+                            Disconnect
+                        }
                     }
-                }
-            }));
-            progress.update(
-                self.chain_pulls
-                    .poll()
-                    .unwrap()
-                    .map(|maybe_item| match maybe_item {
+                }));
+            }
+            if let Async::Ready(()) = self.request_limit.poll_ready() {
+                progress.update(self.chain_pulls.poll().unwrap().map(
+                    |maybe_item| match maybe_item {
                         Some(req) => {
                             // FIXME: implement two-stage chain pull processing
                             // in the blockchain task and use pull_headers here.
@@ -601,8 +619,9 @@ where
                             debug!(self.logger, "outbound header pull stream closed");
                             Disconnect
                         }
-                    }),
-            );
+                    },
+                ));
+            }
 
             match progress {
                 Progress(None) => return Ok(Async::NotReady),
@@ -0,0 +1,106 @@
+use futures::prelude::*;
+use futures::task::AtomicTask;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Bounds the number of requests that a client connection keeps
+/// in flight towards the peer.
+///
+/// Requests beyond the limit are not started until one of the requests
+/// in flight completes; meanwhile, they wait in the outbound
+/// subscription queue of the peer.
+#[derive(Clone)]
+pub struct RequestLimit {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    max: usize,
+    in_flight: AtomicUsize,
+    task: AtomicTask,
+}
+
+/// Accounts for a request in flight. The slot is released when
+/// the guard is dropped.
+pub struct RequestGuard {
+    inner: Arc<Inner>,
+}
+
+impl RequestLimit {
+    pub fn new(max: usize) -> Self {
+        RequestLimit {
+            inner: Arc::new(Inner {
+                max,
+                in_flight: AtomicUsize::new(0),
+                task: AtomicTask::new(),
+            }),
+        }
+    }
+
+    /// Checks if another request can be started.
+    ///
+    /// If the limit has been reached, the current task is scheduled to be
+    /// woken up when one of the requests in flight completes.
+    pub fn poll_ready(&self) -> Async<()> {
+        if self.has_capacity() {
+            return Async::Ready(());
+        }
+        self.inner.task.register();
+        // Check again in case a request has completed before
+        // the task was registered.
+        if self.has_capacity() {
+            Async::Ready(())
+        } else {
+            Async::NotReady
+        }
+    }
+
+    /// Registers a new request in flight.
+    pub fn start_request(&self) -> RequestGuard {
+        self.inner.in_flight.fetch_add(1, Ordering::AcqRel);
+        RequestGuard {
+            inner: self.inner.clone(),
+        }
+    }
+
+    fn has_capacity(&self) -> bool {
+        self.inner.in_flight.load(Ordering::Acquire) < self.inner.max
+    }
+}
+
+impl Drop for RequestGuard {
+    fn drop(&mut self) {
+        self.inner.in_flight.fetch_sub(1, Ordering::AcqRel);
+        self.inner.task.notify();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::future;
+
+    #[test]
+    fn requests_beyond_limit_wait_for_a_slot() {
+        future::lazy(|| {
+            let limit = RequestLimit::new(2);
+            assert_eq!(limit.poll_ready(), Async::Ready(()));
+            let first = limit.start_request();
+            assert_eq!(limit.poll_ready(), Async::Ready(()));
+            let second = limit.start_request();
+            assert_eq!(limit.poll_ready(), Async::NotReady);
+
+            drop(first);
+            assert_eq!(limit.poll_ready(), Async::Ready(()));
+            let _third = limit.start_request();
+            assert_eq!(limit.poll_ready(), Async::NotReady);
+
+            drop(second);
+            assert_eq!(limit.poll_ready(), Async::Ready(()));
+            Ok::<(), ()>(())
+        })
+        .wait()
+        .unwrap();
+    }
+}
@@ -0,0 +1,117 @@
+//! Notifications about the network activity of the node.
+//!
+//! The events are delivered on a best-effort basis: if a subscriber
+//! does not keep up with the events, the events that do not fit in its
+//! buffer are dropped for that subscriber.
+
+use super::p2p::Id;
+use crate::stats_counter::StatsCounter;
+use crate::utils::lock::PoisonRecovery;
+use futures::sync::mpsc;
+use slog::Logger;
+
+use std::mem;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// The kind of subscription stream opened by a peer.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SubscriptionKind {
+    BlockEvents,
+    Fragments,
+    Gossip,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NetworkEvent {
+    /// A connection with the peer has been established, either as a client
+    /// or by the peer subscribing to a stream served by this node.
+    PeerConnected(Id),
+    /// The peer has been disconnected, after failing to receive items,
+    /// for not taking items from its subscriptions, or to make room
+    /// for another peer.
+    PeerDisconnected(Id),
+    /// The peer has subscribed to a stream served by this node.
+    SubscriptionAdded { node_id: Id, kind: SubscriptionKind },
+    /// The initial bootstrap from the given peer has completed.
+    BootstrapCompleted(SocketAddr),
+}
+
+/// Fans out network events to all subscribers.
+#[derive(Clone)]
+pub struct EventBus {
+    subscribers: Arc<Mutex<Vec<mpsc::Sender<NetworkEvent>>>>,
+    poison_recovery: PoisonRecovery,
+}
+
+impl EventBus {
+    pub fn new(stats_counter: StatsCounter, logger: Logger) -> Self {
+        EventBus {
+            subscribers: Arc::new(Mutex::new(Vec::new())),
+            poison_recovery: PoisonRecovery::new("network events", stats_counter, logger),
+        }
+    }
+
+    fn subscribers(&self) -> MutexGuard<Vec<mpsc::Sender<NetworkEvent>>> {
+        self.poison_recovery.recover(self.subscribers.lock())
+    }
+
+    /// Returns a stream of the events emitted after this call.
+    /// Up to `buffer` events are kept for the subscriber if it
+    /// does not consume them in time; further events are dropped.
+    ///
+    /// This is meant for the application embedding the node,
+    /// the node itself does not subscribe to its events.
+    #[allow(dead_code)]
+    pub fn subscribe(&self, buffer: usize) -> mpsc::Receiver<NetworkEvent> {
+        let (tx, rx) = mpsc::channel(buffer);
+        self.subscribers().push(tx);
+        rx
+    }
+
+    pub fn emit(&self, event: NetworkEvent) {
+        let mut subscribers = self.subscribers();
+        // Subscribers that have dropped their receiver are removed,
+        // subscribers that are lagging behind miss the event.
+        let senders = mem::replace(&mut *subscribers, Vec::new());
+        *subscribers = senders
+            .into_iter()
+            .filter_map(|mut tx| match tx.try_send(event.clone()) {
+                Err(ref e) if e.is_disconnected() => None,
+                _ => Some(tx),
+            })
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{Future, Stream};
+    use std::thread;
+
+    #[test]
+    fn events_are_emitted_after_lock_poisoning() {
+        let stats_counter = StatsCounter::default();
+        let events = EventBus::new(stats_counter.clone(), Logger::root(slog::Discard, o!()));
+        let receiver = events.subscribe(4);
+
+        let res = {
+            let events = events.clone();
+            thread::spawn(move || {
+                let _subscribers = events.subscribers.lock().unwrap();
+                panic!("poisoning the lock");
+            })
+            .join()
+        };
+        assert!(res.is_err());
+
+        let address = "127.0.0.1:3000".parse().unwrap();
+        events.emit(NetworkEvent::BootstrapCompleted(address));
+        assert_eq!(stats_counter.lock_poisoned_cnt(), 1);
+
+        drop(events);
+        let received = receiver.collect().wait().unwrap();
+        assert_eq!(received, vec![NetworkEvent::BootstrapCompleted(address)]);
+    }
+}
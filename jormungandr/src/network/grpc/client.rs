@@ -1,7 +1,7 @@
 use crate::{
     blockcfg::{Block, HeaderHash},
     network::{p2p::Id, BlockConfig},
-    settings::start::network::Peer,
+    settings::start::network::{Peer, TcpOptions},
 };
 use futures::prelude::*;
 use http::{HttpTryFrom, Uri};
@@ -38,10 +38,16 @@ pub type ConnectFuture =
     network_grpc::client::ConnectFuture<BlockConfig, HttpConnector, TaskExecutor>;
 pub type ConnectError = network_grpc::client::ConnectError<io::Error>;
 
-pub fn connect(addr: SocketAddr, node_id: Option<Id>, executor: TaskExecutor) -> ConnectFuture {
+pub fn connect(
+    addr: SocketAddr,
+    node_id: Option<Id>,
+    tcp: &TcpOptions,
+    executor: TaskExecutor,
+) -> ConnectFuture {
     let uri = destination_uri(addr);
     let mut connector = HttpConnector::new(2);
-    connector.set_nodelay(true);
+    connector.set_nodelay(tcp.nodelay);
+    connector.set_keepalive(tcp.keepalive);
     let mut builder = Connect::new(connector, executor);
     if let Some(id) = node_id {
         builder.node_id(id);
@@ -63,11 +69,12 @@ fn destination_uri(addr: SocketAddr) -> Uri {
 pub fn fetch_block(
     peer: Peer,
     hash: HeaderHash,
+    tcp: &TcpOptions,
     logger: &Logger,
 ) -> Result<Block, FetchBlockError> {
     info!(logger, "fetching block {}", hash);
     let runtime = Runtime::new().map_err(|e| FetchBlockError::RuntimeInit { source: e })?;
-    let fetch = connect(peer.address(), None, runtime.executor())
+    let fetch = connect(peer.address(), None, tcp, runtime.executor())
         .map_err(|err| FetchBlockError::Connect { source: err })
         .and_then(move |client: Connection| {
             client
@@ -1,10 +1,13 @@
 use super::super::{service::NodeService, Channels, GlobalStateR, ListenError};
-use crate::settings::start::network::Listen;
+use crate::settings::start::network::{Listen, TcpOptions};
 use network_grpc::server::{self, Server};
 
 use tk_listen::ListenExt;
+use tokio::net::TcpStream;
 use tokio::prelude::*;
 
+use std::io;
+
 pub fn run_listen_socket(
     listen: &Listen,
     state: GlobalStateR,
@@ -21,6 +24,7 @@ pub fn run_listen_socket(
         Err(e) => Err(ListenError { cause: e, sockaddr }),
         Ok(listener_stream) => {
             let max_connections = state.config.max_connections;
+            let tcp = state.config.tcp;
             let fold_logger = state.logger().clone();
             let err_logger = state.logger().clone();
             let node_server = NodeService::new(channels, state);
@@ -51,6 +55,13 @@ pub fn run_listen_socket(
                         "incoming connection on {}",
                         stream.local_addr().unwrap(),
                     );
+                    if let Err(e) = set_socket_options(&stream, &tcp) {
+                        warn!(
+                            conn_logger,
+                            "failed to set socket options on the connection";
+                            "reason" => %e,
+                        );
+                    }
 
                     let conn = server.serve(stream).then(move |res| {
                         use network_grpc::server::Error;
@@ -84,3 +95,44 @@ pub fn run_listen_socket(
         }
     }
 }
+
+fn set_socket_options(stream: &TcpStream, tcp: &TcpOptions) -> io::Result<()> {
+    stream.set_nodelay(tcp.nodelay)?;
+    stream.set_keepalive(tcp.keepalive)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::net;
+    use std::time::Duration;
+    use tokio::reactor::Handle;
+
+    fn accepted_stream() -> (TcpStream, net::TcpStream) {
+        let listener = net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = net::TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (stream, _) = listener.accept().unwrap();
+        let stream = TcpStream::from_std(stream, &Handle::default()).unwrap();
+        (stream, client)
+    }
+
+    #[test]
+    fn socket_options_are_applied() {
+        let (stream, _client) = accepted_stream();
+        let tcp = TcpOptions {
+            nodelay: true,
+            keepalive: Some(Duration::from_secs(30)),
+        };
+        set_socket_options(&stream, &tcp).unwrap();
+        assert!(stream.nodelay().unwrap());
+        assert_eq!(stream.keepalive().unwrap(), Some(Duration::from_secs(30)));
+
+        let tcp = TcpOptions {
+            nodelay: false,
+            keepalive: None,
+        };
+        set_socket_options(&stream, &tcp).unwrap();
+        assert!(!stream.nodelay().unwrap());
+        assert_eq!(stream.keepalive().unwrap(), None);
+    }
+}
@@ -7,6 +7,7 @@
 
 pub mod bootstrap;
 mod client;
+pub mod events;
 mod grpc;
 mod inbound;
 pub mod p2p;
@@ -29,7 +30,12 @@ mod buffer_sizes {
     pub const FRAGMENTS: usize = 128;
 }
 
+// Interval between the checks for peers that stopped receiving items
+// from their subscriptions.
+const STALL_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
 use self::client::ConnectError;
+use self::events::{EventBus, NetworkEvent};
 use self::p2p::{
     comm::{PeerComms, Peers},
     P2pTopology,
@@ -38,6 +44,7 @@ use crate::blockcfg::{Block, HeaderHash};
 use crate::blockchain::{Blockchain as NewBlockchain, Tip};
 use crate::intercom::{BlockMsg, ClientMsg, NetworkMsg, PropagateMsg, TransactionMsg};
 use crate::settings::start::network::{Configuration, Peer, Protocol};
+use crate::stats_counter::StatsCounter;
 use crate::utils::{
     async_msg::{MessageBox, MessageQueue},
     task::{TaskMessageBox, TokioServiceInfo},
@@ -106,6 +113,7 @@ pub struct GlobalState {
     pub config: Configuration,
     pub topology: P2pTopology,
     pub peers: Peers,
+    pub events: EventBus,
     pub executor: TaskExecutor,
     pub logger: Logger,
 }
@@ -117,10 +125,16 @@ impl GlobalState {
     pub fn new(
         block0_hash: HeaderHash,
         config: Configuration,
+        events: EventBus,
+        stats_counter: StatsCounter,
         executor: TaskExecutor,
         logger: Logger,
     ) -> Self {
-        let mut topology = P2pTopology::new(config.profile.clone(), logger.clone());
+        let mut topology = P2pTopology::new(
+            config.profile.clone(),
+            stats_counter.clone(),
+            logger.clone(),
+        );
         topology.set_poldercast_modules();
 
         // inject the trusted peers as initial gossips, this will make the node
@@ -142,13 +156,22 @@ impl GlobalState {
                 .into(),
         );
 
-        let peers = Peers::new(config.max_connections, logger.clone());
+        let peers = Peers::new(
+            config.max_connections,
+            config.eviction_policy,
+            config.subscription_failure_limit,
+            config.subscription_failure_window,
+            stats_counter,
+            events.clone(),
+            logger.clone(),
+        );
 
         GlobalState {
             block0_hash,
             config,
             topology,
             peers,
+            events,
             executor,
             logger,
         }
@@ -199,6 +222,8 @@ pub struct TaskParams {
     pub block0_hash: HeaderHash,
     pub input: MessageQueue<NetworkMsg>,
     pub channels: Channels,
+    pub events: EventBus,
+    pub stats_counter: StatsCounter,
 }
 
 pub fn start(
@@ -213,10 +238,23 @@ pub fn start(
     let global_state = Arc::new(GlobalState::new(
         params.block0_hash,
         params.config,
+        params.events,
+        params.stats_counter,
         service_info.executor().clone(),
         service_info.logger().clone(),
     ));
 
+    // A node with neither a listen address nor trusted peers can only run
+    // in isolation, e.g. as the sole BFT leader of a test network.
+    // Keep the task running so that the other tasks can still send to it.
+    if global_state.config.is_isolated() {
+        warn!(
+            service_info.logger(),
+            "no listen address and no trusted peers are configured, \
+             the node will not connect to any peers"
+        );
+    }
+
     // open the port for listening/accepting other peers to connect too
     let listen = global_state.config.listen();
     use futures::future::Either;
@@ -251,6 +289,18 @@ pub fn start(
 
     let handle_cmds = handle_network_input(input, global_state.clone(), channels.clone());
 
+    let watchdog_state = global_state.clone();
+    let watchdog_err_logger = global_state.logger.clone();
+    let stall_timeout = global_state.config.subscription_stall_timeout;
+    let watchdog = Interval::new_interval(STALL_CHECK_INTERVAL)
+        .map_err(move |e| {
+            error!(watchdog_err_logger, "interval timer error: {:?}", e);
+        })
+        .for_each(move |_| {
+            watchdog_state.peers.evict_stalled(stall_timeout);
+            Ok(())
+        });
+
     let gossip_err_logger = global_state.logger.clone();
     // TODO: get gossip propagation interval from configuration
     let gossip = Interval::new_interval(Duration::from_secs(10))
@@ -262,7 +312,7 @@ pub fn start(
             Ok(())
         });
 
-    listener.join3(handle_cmds, gossip).map(|_| ())
+    listener.join4(handle_cmds, gossip, watchdog).map(|_| ())
 }
 
 fn handle_network_input(
@@ -365,9 +415,16 @@ fn connect_and_propagate_with<F>(
     let conn_logger = conn_state
         .logger()
         .new(o!("node_id" => node_id.to_string()));
-    info!(conn_logger, "connecting to peer");
     let (handle, connecting) = client::connect(conn_state, channels.clone());
-    state.peers.connecting_with(node_id, handle, modify_comms);
+    if !state.peers.connecting_with(node_id, handle, modify_comms) {
+        // The item that was to be sent once connected is dropped here.
+        info!(
+            conn_logger,
+            "not connecting to peer: the limit on connections is reached, dropping the item to send"
+        );
+        return;
+    }
+    info!(conn_logger, "connecting to peer");
     let spawn_state = state.clone();
     let conn_err_state = state.clone();
     let cf = connecting
@@ -406,10 +463,18 @@ fn connect_and_propagate_with<F>(
                     return Err(());
                 }
                 if let Some(comms) = state.peers.remove_peer(node_id) {
-                    state.peers.insert_peer(connected_node_id, comms);
+                    if !state.peers.insert_peer(connected_node_id, comms) {
+                        info!(
+                            client.logger(),
+                            "no room for peer {} under its node ID", connected_node_id
+                        );
+                        return Err(());
+                    }
                 } else {
                     warn!(client.logger(), "peer no longer in map after connecting");
                 }
+            } else {
+                state.events.emit(NetworkEvent::PeerConnected(node_id));
             }
             Ok(client)
         })
@@ -432,6 +497,7 @@ pub fn bootstrap(
     config: &Configuration,
     blockchain: NewBlockchain,
     branch: Tip,
+    events: &EventBus,
     logger: &Logger,
 ) -> Result<bool, bootstrap::Error> {
     if config.protocol != Protocol::Grpc {
@@ -442,34 +508,61 @@ pub fn bootstrap(
         warn!(logger, "No trusted peers joinable to bootstrap the network");
     }
 
-    let mut bootstrapped = false;
+    let bootstrapped = bootstrap_from_peers(
+        trusted_peers_shuffled(&config),
+        logger,
+        |address, logger| {
+            let peer = Peer::new(address, Protocol::Grpc);
+            bootstrap::bootstrap_from_peer(
+                peer,
+                config,
+                blockchain.clone(),
+                branch.clone(),
+                logger.clone(),
+            )
+        },
+    );
 
-    for address in trusted_peers_shuffled(&config) {
-        let logger = logger.new(o!("peer_addr" => address.to_string()));
-        let peer = Peer::new(address, Protocol::Grpc);
-        let res = bootstrap::bootstrap_from_peer(
-            peer,
-            blockchain.clone(),
-            branch.clone(),
-            logger.clone(),
-        );
+    if let Some(address) = bootstrapped {
+        events.emit(NetworkEvent::BootstrapCompleted(address));
+    }
 
-        match res {
+    Ok(bootstrapped.is_some())
+}
+
+// Tries to bootstrap from each of the peers in turn, until one succeeds.
+// Returns the address of the peer that the node has bootstrapped from.
+fn bootstrap_from_peers<I, F, T>(
+    addresses: I,
+    logger: &Logger,
+    mut bootstrap_from: F,
+) -> Option<SocketAddr>
+where
+    I: IntoIterator<Item = SocketAddr>,
+    F: FnMut(SocketAddr, &Logger) -> Result<T, bootstrap::Error>,
+{
+    for address in addresses {
+        let logger = logger.new(o!("peer_addr" => address.to_string()));
+        match bootstrap_from(address, &logger) {
             Err(bootstrap::Error::Connect { source: e }) => {
                 warn!(logger, "unable to reach peer for initial bootstrap"; "reason" => %e);
             }
+            Err(bootstrap::Error::Timeout) => {
+                warn!(logger, "initial bootstrap from peer timed out");
+            }
+            Err(bootstrap::Error::Stalled) => {
+                warn!(logger, "initial bootstrap peer stopped sending blocks");
+            }
             Err(e) => {
                 warn!(logger, "initial bootstrap failed"; "error" => ?e);
             }
             Ok(_) => {
                 info!(logger, "initial bootstrap completed");
-                bootstrapped = true;
-                break;
+                return Some(address);
             }
         }
     }
-
-    Ok(bootstrapped)
+    None
 }
 
 /// Queries the trusted peers for a block identified with the hash.
@@ -496,7 +589,7 @@ pub fn fetch_block(
     for address in trusted_peers_shuffled(&config) {
         let logger = logger.new(o!("peer_address" => address.to_string()));
         let peer = Peer::new(address, Protocol::Grpc);
-        match grpc::fetch_block(peer, hash, &logger) {
+        match grpc::fetch_block(peer, hash, &config.tcp, &logger) {
             Err(grpc::FetchBlockError::Connect { source: e }) => {
                 warn!(logger, "unable to reach peer for block download"; "reason" => %e);
             }
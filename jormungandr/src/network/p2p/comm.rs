@@ -3,8 +3,12 @@ mod peer_map;
 use crate::blockcfg::{Block, Fragment, Header, HeaderHash};
 use crate::network::{
     client::ConnectHandle,
+    events::{EventBus, NetworkEvent},
     p2p::{Gossip as NodeData, Id, Node as NodeRef},
 };
+use crate::settings::start::network::EvictionPolicy;
+use crate::stats_counter::{PeerDropReason, StatsCounter};
+use crate::utils::lock::PoisonRecovery;
 use futures::prelude::*;
 use futures::stream;
 use futures::sync::mpsc;
@@ -15,8 +19,9 @@ use slog::Logger;
 
 use std::fmt;
 use std::mem;
-use std::sync::Mutex;
-use std::time::SystemTime;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant, SystemTime};
 
 // Buffer size determines the number of stream items pending processing that
 // can be buffered before back pressure is applied to the inbound half of
@@ -63,6 +68,7 @@ impl fmt::Display for ErrorKind {
 /// Stream used as the outbound half of a subscription stream.
 pub struct OutboundSubscription<T> {
     inner: mpsc::Receiver<T>,
+    delivered: Arc<AtomicUsize>,
 }
 
 impl<T> Stream for OutboundSubscription<T> {
@@ -70,7 +76,11 @@ impl<T> Stream for OutboundSubscription<T> {
     type Error = core_error::Error;
 
     fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
-        Ok(self.inner.poll().unwrap())
+        let polled = self.inner.poll().unwrap();
+        if let Async::Ready(Some(_)) = polled {
+            self.delivered.fetch_add(1, Ordering::Relaxed);
+        }
+        Ok(polled)
     }
 }
 
@@ -94,12 +104,19 @@ pub type BlockEventSubscription = stream::Select<
 /// subscription stream towards the peer.
 pub struct CommHandle<T> {
     state: SubscriptionState<T>,
+    // Number of items queued into the current subscription stream
+    queued: usize,
+    // Number of items taken from the subscription stream for sending,
+    // updated by the stream
+    delivered: Arc<AtomicUsize>,
 }
 
 impl<T> Default for CommHandle<T> {
     fn default() -> Self {
         CommHandle {
             state: SubscriptionState::NotSubscribed,
+            queued: 0,
+            delivered: Arc::new(AtomicUsize::new(0)),
         }
     }
 }
@@ -110,6 +127,7 @@ impl<T> CommHandle<T> {
     pub fn pending(item: T) -> Self {
         CommHandle {
             state: SubscriptionState::Pending(item),
+            ..Default::default()
         }
     }
 
@@ -124,6 +142,8 @@ impl<T> CommHandle<T> {
     /// to the same peer. This method is used instead of replacing
     /// the handle to send a potential pending item over the new subscription.
     pub fn update(&mut self, newer: CommHandle<T>) {
+        self.queued = newer.queued;
+        self.delivered = newer.delivered;
         match mem::replace(&mut self.state, newer.state) {
             SubscriptionState::Pending(item) => {
                 // If there is an error sending the pending item,
@@ -144,11 +164,18 @@ impl<T> CommHandle<T> {
         use self::SubscriptionState::*;
 
         let (mut tx, rx) = mpsc::channel(BUFFER_LEN);
+        let delivered = Arc::new(AtomicUsize::new(0));
+        self.queued = 0;
         if let Pending(item) = mem::replace(&mut self.state, NotSubscribed) {
             tx.try_send(item).unwrap();
+            self.queued = 1;
         }
         self.state = Subscribed(tx);
-        OutboundSubscription { inner: rx }
+        self.delivered = delivered.clone();
+        OutboundSubscription {
+            inner: rx,
+            delivered,
+        }
     }
 
     pub fn is_subscribed(&self) -> bool {
@@ -176,8 +203,12 @@ impl<T> CommHandle<T> {
                 *pending = item;
                 Ok(())
             }
-            SubscriptionState::Subscribed(ref mut sender) => sender.try_send(item).map_err(|e| {
-                if e.is_disconnected() {
+            SubscriptionState::Subscribed(ref mut sender) => match sender.try_send(item) {
+                Ok(()) => {
+                    self.queued += 1;
+                    Ok(())
+                }
+                Err(e) => Err(if e.is_disconnected() {
                     PropagateError {
                         kind: ErrorKind::SubscriptionClosed,
                         item: e.into_inner(),
@@ -192,8 +223,23 @@ impl<T> CommHandle<T> {
                         kind: ErrorKind::Unexpected,
                         item: e.into_inner(),
                     }
-                }
-            }),
+                }),
+            },
+        }
+    }
+
+    // Number of items that have been taken from the subscription stream
+    // to be sent to the peer.
+    fn delivered(&self) -> usize {
+        self.delivered.load(Ordering::Relaxed)
+    }
+
+    // Checks if there are items queued in the subscription stream
+    // that have not been taken for sending yet.
+    fn has_backlog(&self) -> bool {
+        match self.state {
+            SubscriptionState::Subscribed(_) => self.queued > self.delivered(),
+            SubscriptionState::NotSubscribed | SubscriptionState::Pending(_) => false,
         }
     }
 }
@@ -309,6 +355,27 @@ impl PeerComms {
     pub fn gossip_subscribed(&self) -> bool {
         self.gossip.is_subscribed()
     }
+
+    /// Returns the total number of items taken for sending to the peer
+    /// from all outbound subscription streams.
+    pub fn items_delivered(&self) -> usize {
+        self.block_announcements
+            .delivered()
+            .wrapping_add(self.block_solicitations.delivered())
+            .wrapping_add(self.chain_pulls.delivered())
+            .wrapping_add(self.fragments.delivered())
+            .wrapping_add(self.gossip.delivered())
+    }
+
+    /// Checks if any of the outbound subscription streams has items
+    /// waiting to be sent to the peer.
+    pub fn has_backlog(&self) -> bool {
+        self.block_announcements.has_backlog()
+            || self.block_solicitations.has_backlog()
+            || self.chain_pulls.has_backlog()
+            || self.fragments.has_backlog()
+            || self.gossip.has_backlog()
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -367,39 +434,104 @@ impl PeerStats {
 /// all network connection tasks.
 pub struct Peers {
     mutex: Mutex<peer_map::PeerMap>,
+    poison_recovery: PoisonRecovery,
+    failure_limit: usize,
+    failure_window: Duration,
+    stats_counter: StatsCounter,
+    events: EventBus,
     logger: Logger,
 }
 
 impl Peers {
-    pub fn new(capacity: usize, logger: Logger) -> Self {
+    pub fn new(
+        capacity: usize,
+        eviction_policy: EvictionPolicy,
+        failure_limit: usize,
+        failure_window: Duration,
+        stats_counter: StatsCounter,
+        events: EventBus,
+        logger: Logger,
+    ) -> Self {
         Peers {
-            mutex: Mutex::new(peer_map::PeerMap::new(capacity)),
+            mutex: Mutex::new(peer_map::PeerMap::new(
+                capacity,
+                eviction_policy,
+                stats_counter.clone(),
+                events.clone(),
+            )),
+            poison_recovery: PoisonRecovery::new("peers", stats_counter.clone(), logger.clone()),
+            failure_limit,
+            failure_window,
+            stats_counter,
+            events,
             logger,
         }
     }
 
-    pub fn insert_peer(&self, id: Id, comms: PeerComms) {
-        let mut map = self.mutex.lock().unwrap();
-        map.insert_peer(id, comms)
+    fn inner(&self) -> MutexGuard<peer_map::PeerMap> {
+        self.poison_recovery.recover(self.mutex.lock())
+    }
+
+    fn dropped(&self, id: Id, reason: PeerDropReason) {
+        self.stats_counter.add_peer_drop_cnt(reason, 1);
+        self.events.emit(NetworkEvent::PeerDisconnected(id));
+    }
+
+    // Returns the communication handles for serving subscriptions
+    // to the peer, notifying about the peer if it is not connected yet.
+    fn server_comms<'a>(
+        &self,
+        map: &'a mut peer_map::PeerMap,
+        id: Id,
+    ) -> Option<&'a mut PeerComms> {
+        let is_new = map.entry(id).is_none();
+        let comms = map.server_comms(id)?;
+        if is_new {
+            self.events.emit(NetworkEvent::PeerConnected(id));
+        }
+        Some(comms)
+    }
+
+    /// Adds or replaces the peer entry, notifying about the peer if it
+    /// was not connected yet. Returns false if the peer has been rejected
+    /// because the limit on connections is reached.
+    pub fn insert_peer(&self, id: Id, comms: PeerComms) -> bool {
+        let mut map = self.inner();
+        let is_new = map.entry(id).is_none();
+        if !map.insert_peer(id, comms) {
+            return false;
+        }
+        if is_new {
+            self.events.emit(NetworkEvent::PeerConnected(id));
+        }
+        true
     }
 
-    pub fn connecting_with<F>(&self, id: Id, handle: ConnectHandle, modify_comms: F)
+    /// Registers a client connection in progress to the peer.
+    /// Returns false if the peer has been rejected because the limit
+    /// on connections is reached.
+    pub fn connecting_with<F>(&self, id: Id, handle: ConnectHandle, modify_comms: F) -> bool
     where
         F: FnOnce(&mut PeerComms),
     {
-        let mut map = self.mutex.lock().unwrap();
-        let comms = map.add_connecting(id, handle);
-        modify_comms(comms);
+        let mut map = self.inner();
+        match map.add_connecting(id, handle) {
+            Some(comms) => {
+                modify_comms(comms);
+                true
+            }
+            None => false,
+        }
     }
 
     pub fn remove_peer(&self, id: Id) -> Option<PeerComms> {
-        let mut map = self.mutex.lock().unwrap();
+        let mut map = self.inner();
         map.remove_peer(id)
     }
 
-    pub fn serve_block_events(&self, id: Id) -> BlockEventSubscription {
-        let mut map = self.mutex.lock().unwrap();
-        let handles = map.server_comms(id);
+    pub fn serve_block_events(&self, id: Id) -> Option<BlockEventSubscription> {
+        let mut map = self.inner();
+        let handles = self.server_comms(&mut map, id)?;
         let announce_events: BlockEventAnnounceStream = handles
             .block_announcements
             .subscribe()
@@ -410,35 +542,62 @@ impl Peers {
             .map(BlockEvent::Solicit);
         let missing_events: BlockEventMissingStream =
             handles.chain_pulls.subscribe().map(BlockEvent::Missing);
-        announce_events
+        let subscription = announce_events
             .select(solicit_events)
-            .select(missing_events)
+            .select(missing_events);
+        Some(subscription)
     }
 
-    pub fn serve_fragments(&self, id: Id) -> OutboundSubscription<Fragment> {
-        let mut map = self.mutex.lock().unwrap();
-        let handles = map.server_comms(id);
-        handles.fragments.subscribe()
+    pub fn serve_fragments(&self, id: Id) -> Option<OutboundSubscription<Fragment>> {
+        let mut map = self.inner();
+        let handles = self.server_comms(&mut map, id)?;
+        Some(handles.fragments.subscribe())
     }
 
-    pub fn serve_gossip(&self, id: Id) -> OutboundSubscription<Gossip<NodeData>> {
-        let mut map = self.mutex.lock().unwrap();
-        let handles = map.server_comms(id);
-        handles.gossip.subscribe()
+    pub fn serve_gossip(&self, id: Id) -> Option<OutboundSubscription<Gossip<NodeData>>> {
+        let mut map = self.inner();
+        let handles = self.server_comms(&mut map, id)?;
+        Some(handles.gossip.subscribe())
     }
 
-    fn propagate_with<T, F>(&self, nodes: Vec<NodeRef>, f: F) -> Result<(), Vec<NodeRef>>
+    // Propagates an item to the peers with the function, which returns
+    // whether the item has been sent or the peer has been skipped.
+    fn propagate_with<N, T, F>(&self, nodes: Vec<N>, f: F) -> Result<(), Vec<N>>
     where
-        F: Fn(&mut PeerComms) -> Result<(), PropagateError<T>>,
+        N: Node<Id = Id>,
+        F: Fn(&mut peer_map::Entry) -> Result<bool, PropagateError<T>>,
     {
-        let mut map = self.mutex.lock().unwrap();
+        let mut map = self.inner();
         let unreached_nodes = nodes
             .into_iter()
             .filter(|node| {
                 let id = node.id();
                 if let Some(mut entry) = map.entry(id) {
-                    match f(entry.updated_comms()) {
-                        Ok(()) => false,
+                    match f(&mut entry) {
+                        Ok(sent) => {
+                            // Skipping a peer says nothing about whether
+                            // its subscription is still overflowing.
+                            if sent {
+                                entry.clear_send_failures();
+                            }
+                            false
+                        }
+                        // A full subscription buffer may be a passing
+                        // hiccup, so the peer is only disconnected if
+                        // this happens repeatedly.
+                        Err(ref e)
+                            if e.kind() == ErrorKind::StreamOverflow
+                                && entry.add_send_failure(self.failure_window)
+                                    < self.failure_limit =>
+                        {
+                            debug!(
+                                self.logger,
+                                "propagation to peer failed, dropping the item";
+                                "node_id" => %id,
+                                "reason" => %e.kind()
+                            );
+                            false
+                        }
                         Err(e) => {
                             debug!(
                                 self.logger,
@@ -447,6 +606,7 @@ impl Peers {
                                 "reason" => %e.kind()
                             );
                             entry.remove();
+                            self.dropped(id, PeerDropReason::SendError);
                             true
                         }
                     }
@@ -462,14 +622,27 @@ impl Peers {
         }
     }
 
-    pub fn propagate_block(&self, nodes: Vec<NodeRef>, header: Header) -> Result<(), Vec<NodeRef>> {
+    pub fn propagate_block<N>(&self, nodes: Vec<N>, header: Header) -> Result<(), Vec<N>>
+    where
+        N: Node<Id = Id>,
+    {
         debug!(
             self.logger,
             "propagating block";
             "hash" => %header.hash(),
         );
-        self.propagate_with(nodes, |handles| {
-            handles.try_send_block_announcement(header.clone())
+        let hash = header.hash();
+        self.propagate_with(nodes, |entry| {
+            // Don't announce the block to a peer that has announced it
+            // to us or has already been sent the announcement.
+            if entry.knows_header(&hash) {
+                return Ok(false);
+            }
+            entry
+                .updated_comms()
+                .try_send_block_announcement(header.clone())?;
+            entry.add_known_header(hash);
+            Ok(true)
         })
     }
 
@@ -482,7 +655,12 @@ impl Peers {
             self.logger,
             "propagating fragment";
         );
-        self.propagate_with(nodes, |handles| handles.try_send_fragment(fragment.clone()))
+        self.propagate_with(nodes, |entry| {
+            entry
+                .updated_comms()
+                .try_send_fragment(fragment.clone())
+                .map(|()| true)
+        })
     }
 
     pub fn propagate_gossip_to(
@@ -495,7 +673,7 @@ impl Peers {
             "sending gossip";
             "node_id" => %target,
         );
-        let mut map = self.mutex.lock().unwrap();
+        let mut map = self.inner();
         if let Some(mut entry) = map.entry(target) {
             let res = {
                 let handles = entry.updated_comms();
@@ -509,6 +687,7 @@ impl Peers {
                     "reason" => %e.kind(),
                 );
                 entry.remove();
+                self.dropped(target, PeerDropReason::SendError);
                 e.into_item()
             })
         } else {
@@ -516,11 +695,12 @@ impl Peers {
         }
     }
 
-    pub fn refresh_peer_on_block(&self, node_id: Id) -> bool {
-        let mut map = self.mutex.lock().unwrap();
+    pub fn refresh_peer_on_block(&self, node_id: Id, hash: HeaderHash) -> bool {
+        let mut map = self.inner();
         match map.refresh_peer(node_id) {
             Some(stats) => {
                 stats.last_block_received = Some(SystemTime::now());
+                map.add_known_header(node_id, hash);
                 true
             }
             None => false,
@@ -528,7 +708,7 @@ impl Peers {
     }
 
     pub fn refresh_peer_on_fragment(&self, node_id: Id) -> bool {
-        let mut map = self.mutex.lock().unwrap();
+        let mut map = self.inner();
         match map.refresh_peer(node_id) {
             Some(stats) => {
                 stats.last_fragment_received = Some(SystemTime::now());
@@ -539,7 +719,7 @@ impl Peers {
     }
 
     pub fn refresh_peer_on_gossip(&self, node_id: Id) -> bool {
-        let mut map = self.mutex.lock().unwrap();
+        let mut map = self.inner();
         match map.refresh_peer(node_id) {
             Some(stats) => {
                 stats.last_gossip_received = Some(SystemTime::now());
@@ -550,7 +730,7 @@ impl Peers {
     }
 
     pub fn fetch_blocks(&self, hashes: Vec<HeaderHash>) {
-        let mut map = self.mutex.lock().unwrap();
+        let mut map = self.inner();
         if let Some((node_id, comms)) = map.next_peer_for_block_fetch() {
             debug!(self.logger, "fetching blocks from {}", node_id);
             comms
@@ -560,6 +740,7 @@ impl Peers {
                     debug!(self.logger, "block fetch from {} failed: {:?}", node_id, e);
                     debug!(self.logger, "unsubscribing peer {}", node_id);
                     map.remove_peer(node_id);
+                    self.dropped(node_id, PeerDropReason::SendError);
                 });
         } else {
             warn!(self.logger, "no peers to fetch blocks from");
@@ -567,7 +748,7 @@ impl Peers {
     }
 
     pub fn solicit_blocks(&self, node_id: Id, hashes: Vec<HeaderHash>) {
-        let mut map = self.mutex.lock().unwrap();
+        let mut map = self.inner();
         match map.peer_comms(node_id) {
             Some(comms) => {
                 debug!(self.logger, "sending block solicitation to {}", node_id;
@@ -582,6 +763,7 @@ impl Peers {
                         );
                         debug!(self.logger, "unsubscribing peer {}", node_id);
                         map.remove_peer(node_id);
+                        self.dropped(node_id, PeerDropReason::SendError);
                     });
             }
             None => {
@@ -595,7 +777,7 @@ impl Peers {
     }
 
     pub fn pull_headers(&self, node_id: Id, from: Vec<HeaderHash>, to: HeaderHash) {
-        let mut map = self.mutex.lock().unwrap();
+        let mut map = self.inner();
         match map.peer_comms(node_id) {
             Some(comms) => {
                 debug!(self.logger, "pulling headers from {}", node_id;
@@ -610,6 +792,7 @@ impl Peers {
                         );
                         debug!(self.logger, "unsubscribing peer {}", node_id);
                         map.remove_peer(node_id);
+                        self.dropped(node_id, PeerDropReason::SendError);
                     });
             }
             None => {
@@ -623,7 +806,269 @@ impl Peers {
     }
 
     pub fn stats(&self) -> Vec<(Id, PeerStats)> {
-        let map = self.mutex.lock().unwrap();
+        let map = self.inner();
         map.stats()
     }
+
+    /// Removes the peers whose outbound subscriptions have had items
+    /// waiting, with none taken for sending, for longer than `timeout`,
+    /// while other peers were receiving items.
+    pub fn evict_stalled(&self, timeout: Duration) -> Vec<Id> {
+        let mut map = self.inner();
+        let stalled = map.evict_stalled(timeout, Instant::now());
+        for &node_id in &stalled {
+            info!(
+                self.logger,
+                "peer has not been receiving items from its subscriptions, unsubscribing peer";
+                "node_id" => %node_id,
+            );
+            self.dropped(node_id, PeerDropReason::Idle);
+        }
+        stalled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::test_utils;
+    use poldercast::NodeProfileBuilder;
+    use std::thread;
+
+    fn node_id() -> Id {
+        (*NodeProfileBuilder::new().build().id()).into()
+    }
+
+    fn node() -> NodeData {
+        NodeProfileBuilder::new().build().into()
+    }
+
+    fn event_bus() -> EventBus {
+        EventBus::new(StatsCounter::default(), Logger::root(slog::Discard, o!()))
+    }
+
+    fn peers_with(
+        capacity: usize,
+        eviction_policy: EvictionPolicy,
+        stats_counter: &StatsCounter,
+        events: &EventBus,
+    ) -> Peers {
+        Peers::new(
+            capacity,
+            eviction_policy,
+            3,
+            Duration::from_secs(10),
+            stats_counter.clone(),
+            events.clone(),
+            Logger::root(slog::Discard, o!()),
+        )
+    }
+
+    fn peers(capacity: usize, events: &EventBus) -> Peers {
+        peers_with(
+            capacity,
+            EvictionPolicy::Lru,
+            &StatsCounter::default(),
+            events,
+        )
+    }
+
+    fn drop_counts(stats_counter: &StatsCounter) -> [u64; 4] {
+        [
+            stats_counter.peer_drop_cnt(PeerDropReason::SendError),
+            stats_counter.peer_drop_cnt(PeerDropReason::Idle),
+            stats_counter.peer_drop_cnt(PeerDropReason::Evicted),
+            stats_counter.peer_drop_cnt(PeerDropReason::Policy),
+        ]
+    }
+
+    #[test]
+    fn inbound_peer_connected_event() {
+        let events = event_bus();
+        let receiver = events.subscribe(16);
+        let peers = peers(1, &events);
+        let first_id = node_id();
+        let second_id = node_id();
+
+        // Only the first subscription from the peer makes it connected.
+        assert!(peers.serve_fragments(first_id).is_some());
+        assert!(peers.serve_gossip(first_id).is_some());
+        // The least recently active peer makes room for the new one.
+        assert!(peers.serve_fragments(second_id).is_some());
+
+        drop(peers);
+        drop(events);
+        let received = receiver.collect().wait().unwrap();
+        assert_eq!(
+            received,
+            vec![
+                NetworkEvent::PeerConnected(first_id),
+                NetworkEvent::PeerDisconnected(first_id),
+                NetworkEvent::PeerConnected(second_id),
+            ]
+        );
+    }
+
+    #[test]
+    fn inserted_peer_connected_event() {
+        let events = event_bus();
+        let receiver = events.subscribe(16);
+        let peers = peers(4, &events);
+        let (inbound_id, new_id) = (node_id(), node_id());
+
+        assert!(peers.serve_fragments(inbound_id).is_some());
+        // Replacing the entry of a connected peer does not notify again.
+        assert!(peers.insert_peer(inbound_id, PeerComms::new()));
+        assert!(peers.insert_peer(new_id, PeerComms::new()));
+
+        drop(peers);
+        drop(events);
+        let received = receiver.collect().wait().unwrap();
+        assert_eq!(
+            received,
+            vec![
+                NetworkEvent::PeerConnected(inbound_id),
+                NetworkEvent::PeerConnected(new_id),
+            ]
+        );
+    }
+
+    #[test]
+    fn send_error_is_counted() {
+        let stats_counter = StatsCounter::default();
+        let peers = peers_with(4, EvictionPolicy::Lru, &stats_counter, &event_bus());
+        let id = node_id();
+        // The peer is not subscribed to block events.
+        assert!(peers.serve_fragments(id).is_some());
+        peers.solicit_blocks(id, vec![]);
+        assert!(peers.remove_peer(id).is_none());
+        assert_eq!(drop_counts(&stats_counter), [1, 0, 0, 0]);
+    }
+
+    #[test]
+    fn idle_peer_is_counted() {
+        let stats_counter = StatsCounter::default();
+        let peers = peers_with(4, EvictionPolicy::Lru, &stats_counter, &event_bus());
+        let (active_id, idle_id) = (node_id(), node_id());
+        let active_events = peers.serve_block_events(active_id).unwrap();
+        let _idle_events = peers.serve_block_events(idle_id).unwrap();
+        peers.solicit_blocks(active_id, vec![]);
+        peers.solicit_blocks(idle_id, vec![]);
+        assert!(active_events.wait().next().unwrap().is_ok());
+
+        assert_eq!(peers.evict_stalled(Duration::from_secs(0)), vec![idle_id]);
+        assert_eq!(drop_counts(&stats_counter), [0, 1, 0, 0]);
+    }
+
+    #[test]
+    fn eviction_is_counted() {
+        let stats_counter = StatsCounter::default();
+        let peers = peers_with(1, EvictionPolicy::Lru, &stats_counter, &event_bus());
+        assert!(peers.serve_fragments(node_id()).is_some());
+        assert!(peers.serve_fragments(node_id()).is_some());
+        assert_eq!(drop_counts(&stats_counter), [0, 0, 1, 0]);
+    }
+
+    #[test]
+    fn only_refused_subscriptions_count_as_policy() {
+        let stats_counter = StatsCounter::default();
+        let peers = peers_with(1, EvictionPolicy::RejectNew, &stats_counter, &event_bus());
+        assert!(peers.serve_fragments(node_id()).is_some());
+        assert!(peers.serve_fragments(node_id()).is_none());
+        assert_eq!(drop_counts(&stats_counter), [0, 0, 0, 1]);
+
+        // A connection from this node that does not fit is not counted.
+        assert!(!peers.insert_peer(node_id(), PeerComms::new()));
+        assert_eq!(drop_counts(&stats_counter), [0, 0, 0, 1]);
+    }
+
+    // Returns the number of block announcements in the block events
+    // served to a peer, after the peer is disconnected.
+    fn announcements(events: BlockEventSubscription) -> usize {
+        events
+            .wait()
+            .filter(|event| match event {
+                Ok(BlockEvent::Announce(_)) => true,
+                _ => false,
+            })
+            .count()
+    }
+
+    #[test]
+    fn block_is_announced_once_and_not_echoed() {
+        let peers = peers(4, &event_bus());
+        let (sender, other) = (node(), node());
+        let sender_events = peers.serve_block_events(sender.id()).unwrap();
+        let other_events = peers.serve_block_events(other.id()).unwrap();
+        let header = test_utils::genesis_block().header;
+
+        // The block is announced to this node by the sender.
+        assert!(peers.refresh_peer_on_block(sender.id(), header.hash()));
+        let nodes = || vec![sender.clone(), other.clone()];
+        assert!(peers.propagate_block(nodes(), header.clone()).is_ok());
+        assert!(peers.propagate_block(nodes(), header.clone()).is_ok());
+
+        drop(peers);
+        assert_eq!(announcements(sender_events), 0);
+        assert_eq!(announcements(other_events), 1);
+    }
+    #[test]
+    fn skipped_peer_keeps_send_failures() {
+        let stats_counter = StatsCounter::default();
+        let peers = peers_with(4, EvictionPolicy::Lru, &stats_counter, &event_bus());
+        let peer = node();
+        // The block events are not taken from the subscription.
+        let _events = peers.serve_block_events(peer.id()).unwrap();
+        let genesis = test_utils::genesis_block().header;
+        let headers = test_utils::headers(&test_utils::chain(&genesis, BUFFER_LEN + 5));
+        let propagate = |header: &Header| peers.propagate_block(vec![peer.clone()], header.clone());
+
+        // The subscription buffer holds one more item than its size
+        // for the single sender.
+        for header in &headers[..BUFFER_LEN + 1] {
+            assert!(propagate(header).is_ok());
+        }
+        // The first overflows below the limit keep the peer.
+        assert!(propagate(&headers[BUFFER_LEN + 1]).is_ok());
+        assert!(propagate(&headers[BUFFER_LEN + 2]).is_ok());
+        // A header announced by the peer is not sent to it.
+        let known = &headers[BUFFER_LEN + 3];
+        assert!(peers.refresh_peer_on_block(peer.id(), known.hash()));
+        assert!(propagate(known).is_ok());
+        // The skip has not cleared the earlier overflows.
+        assert!(propagate(&headers[BUFFER_LEN + 4]).is_err());
+        assert!(peers.remove_peer(peer.id()).is_none());
+        assert_eq!(drop_counts(&stats_counter), [1, 0, 0, 0]);
+    }
+
+    #[test]
+    fn block_is_propagated_after_lock_poisoning() {
+        let stats_counter = StatsCounter::default();
+        let peers = Arc::new(peers_with(
+            4,
+            EvictionPolicy::Lru,
+            &stats_counter,
+            &event_bus(),
+        ));
+        let peer = node();
+        let events = peers.serve_block_events(peer.id()).unwrap();
+
+        let res = {
+            let peers = peers.clone();
+            thread::spawn(move || {
+                let _guard = peers.mutex.lock().unwrap();
+                panic!("poisoning the peer map lock");
+            })
+            .join()
+        };
+        assert!(res.is_err());
+        assert!(peers.mutex.is_poisoned());
+
+        let header = test_utils::genesis_block().header;
+        assert!(peers.propagate_block(vec![peer], header).is_ok());
+        assert_eq!(stats_counter.lock_poisoned_cnt(), 1);
+
+        drop(peers);
+        assert_eq!(announcements(events), 1);
+    }
 }
@@ -1,16 +1,29 @@
+use crate::blockcfg::HeaderHash;
 use crate::network::{
     client::ConnectHandle,
+    events::{EventBus, NetworkEvent},
     p2p::{
         comm::{PeerComms, PeerStats},
         Id,
     },
 };
+use crate::settings::start::network::EvictionPolicy;
+use crate::stats_counter::{PeerDropReason, StatsCounter};
 
 use linked_hash_map::LinkedHashMap;
 
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+// The number of block headers remembered as known to each peer.
+const KNOWN_HEADERS_CAPACITY: usize = 64;
+
 pub struct PeerMap {
     map: LinkedHashMap<Id, PeerData>,
     capacity: usize,
+    eviction_policy: EvictionPolicy,
+    stats_counter: StatsCounter,
+    events: EventBus,
 }
 
 #[derive(Default)]
@@ -18,14 +31,76 @@ struct PeerData {
     comms: PeerComms,
     stats: PeerStats,
     connecting: Option<ConnectHandle>,
+    watchdog: DeliveryWatchdog,
+    known_headers: KnownHeaders,
+    send_failures: SendFailures,
+}
+
+// Tracks delivery of items to the peer between the stall checks.
+#[derive(Default)]
+struct DeliveryWatchdog {
+    last_delivered: usize,
+    stalled_since: Option<Instant>,
+}
+
+// Hashes of the most recent block headers that the peer is known to have,
+// either because the peer has announced them or because they have been
+// announced to the peer. The oldest hashes are forgotten first.
+#[derive(Default)]
+struct KnownHeaders {
+    hashes: VecDeque<HeaderHash>,
+}
+
+impl KnownHeaders {
+    fn contains(&self, hash: &HeaderHash) -> bool {
+        self.hashes.contains(hash)
+    }
+
+    fn insert(&mut self, hash: HeaderHash) {
+        if self.contains(&hash) {
+            return;
+        }
+        if self.hashes.len() == KNOWN_HEADERS_CAPACITY {
+            self.hashes.pop_front();
+        }
+        self.hashes.push_back(hash);
+    }
+}
+
+// Counts the failures to send items to the peer that occur within
+// a time window from the first one.
+#[derive(Default)]
+struct SendFailures {
+    count: usize,
+    since: Option<Instant>,
+}
+
+impl SendFailures {
+    // Records a failure and returns the number of failures in the window.
+    fn add(&mut self, now: Instant, window: Duration) -> usize {
+        match self.since {
+            Some(since) if now.duration_since(since) < window => {
+                self.count += 1;
+            }
+            _ => {
+                self.since = Some(now);
+                self.count = 1;
+            }
+        }
+        self.count
+    }
+
+    fn clear(&mut self) {
+        self.count = 0;
+        self.since = None;
+    }
 }
 
 impl PeerData {
     fn with_comms(comms: PeerComms) -> Self {
         PeerData {
             comms,
-            stats: PeerStats::default(),
-            connecting: None,
+            ..Default::default()
         }
     }
 
@@ -56,10 +131,18 @@ impl PeerData {
 }
 
 impl PeerMap {
-    pub fn new(capacity: usize) -> Self {
+    pub fn new(
+        capacity: usize,
+        eviction_policy: EvictionPolicy,
+        stats_counter: StatsCounter,
+        events: EventBus,
+    ) -> Self {
         PeerMap {
             map: LinkedHashMap::new(),
             capacity,
+            eviction_policy,
+            stats_counter,
+            events,
         }
     }
 
@@ -76,31 +159,52 @@ impl PeerMap {
         self.map.get_refresh(&id).map(|data| &mut data.stats)
     }
 
+    pub fn add_known_header(&mut self, id: Id, hash: HeaderHash) {
+        if let Some(data) = self.map.get_mut(&id) {
+            data.known_headers.insert(hash);
+        }
+    }
+
     pub fn peer_comms(&mut self, id: Id) -> Option<&mut PeerComms> {
         self.map.get_mut(&id).map(PeerData::updated_comms)
     }
 
-    fn ensure_peer(&mut self, id: Id) -> &mut PeerData {
-        if !self.map.contains_key(&id) {
-            self.evict_if_full();
+    fn ensure_peer(&mut self, id: Id) -> Option<&mut PeerData> {
+        if !self.map.contains_key(&id) && !self.make_room() {
+            return None;
         }
-        self.map.entry(id).or_insert_with(Default::default)
+        Some(self.map.entry(id).or_insert_with(Default::default))
     }
 
-    pub fn server_comms(&mut self, id: Id) -> &mut PeerComms {
-        self.ensure_peer(id).server_comms()
+    /// Returns the communication handles for serving subscriptions
+    /// to the peer, or `None` if the peer has been rejected by the
+    /// eviction policy.
+    pub fn server_comms(&mut self, id: Id) -> Option<&mut PeerComms> {
+        if self.ensure_peer(id).is_none() {
+            // Only the subscriptions refused to inbound peers are
+            // counted, not the connections attempted by this node.
+            self.stats_counter
+                .add_peer_drop_cnt(PeerDropReason::Policy, 1);
+            return None;
+        }
+        self.map.get_mut(&id).map(PeerData::server_comms)
     }
 
-    pub fn insert_peer(&mut self, id: Id, comms: PeerComms) {
-        self.evict_if_full();
+    /// Inserts the peer, replacing any previous entry with the same ID.
+    /// Returns false if the peer has been rejected by the eviction policy.
+    pub fn insert_peer(&mut self, id: Id, comms: PeerComms) -> bool {
+        if !self.map.contains_key(&id) && !self.make_room() {
+            return false;
+        }
         let data = PeerData::with_comms(comms);
         self.map.insert(id, data);
+        true
     }
 
-    pub fn add_connecting(&mut self, id: Id, handle: ConnectHandle) -> &mut PeerComms {
-        let data = self.ensure_peer(id);
+    pub fn add_connecting(&mut self, id: Id, handle: ConnectHandle) -> Option<&mut PeerComms> {
+        let data = self.ensure_peer(id)?;
         data.connecting = Some(handle);
-        data.updated_comms()
+        Some(data.updated_comms())
     }
 
     pub fn remove_peer(&mut self, id: Id) -> Option<PeerComms> {
@@ -126,9 +230,57 @@ impl PeerMap {
             .collect()
     }
 
-    fn evict_if_full(&mut self) {
-        if self.map.len() >= self.capacity {
-            self.map.pop_front();
+    pub fn evict_stalled(&mut self, timeout: Duration, now: Instant) -> Vec<Id> {
+        let mut others_progressed = false;
+        let mut stalled = Vec::new();
+        for (&id, data) in self.map.iter_mut() {
+            let (delivered, has_backlog) = {
+                let comms = data.updated_comms();
+                (comms.items_delivered(), comms.has_backlog())
+            };
+            let watchdog = &mut data.watchdog;
+            if delivered != watchdog.last_delivered {
+                watchdog.last_delivered = delivered;
+                watchdog.stalled_since = None;
+                others_progressed = true;
+            } else if has_backlog {
+                let since = *watchdog.stalled_since.get_or_insert(now);
+                if now.duration_since(since) >= timeout {
+                    stalled.push(id);
+                }
+            } else {
+                watchdog.stalled_since = None;
+            }
+        }
+        // If no peer is receiving anything, the cause is likely
+        // on this side and the peers are not to blame.
+        if !others_progressed {
+            return Vec::new();
+        }
+        for id in &stalled {
+            self.map.remove(id);
+        }
+        stalled
+    }
+
+    // Makes room for a new peer if the map is full, according to
+    // the eviction policy. Returns false if the new peer is to be rejected.
+    fn make_room(&mut self) -> bool {
+        if self.map.len() < self.capacity {
+            return true;
+        }
+        match self.eviction_policy {
+            EvictionPolicy::Lru => {
+                // Peers are moved to the back of the map on activity,
+                // so the front entry is the least recently active one.
+                if let Some((id, _)) = self.map.pop_front() {
+                    self.stats_counter
+                        .add_peer_drop_cnt(PeerDropReason::Evicted, 1);
+                    self.events.emit(NetworkEvent::PeerDisconnected(id));
+                }
+                true
+            }
+            EvictionPolicy::RejectNew => false,
         }
     }
 }
@@ -146,7 +298,175 @@ impl<'a> Entry<'a> {
         &mut self.inner.get_mut().stats
     }
 
+    pub fn knows_header(&self, hash: &HeaderHash) -> bool {
+        self.inner.get().known_headers.contains(hash)
+    }
+
+    pub fn add_known_header(&mut self, hash: HeaderHash) {
+        self.inner.get_mut().known_headers.insert(hash)
+    }
+
+    /// Records a failure to send an item to the peer and returns the number
+    /// of such failures that occurred within `window` from the first one.
+    pub fn add_send_failure(&mut self, window: Duration) -> usize {
+        self.inner
+            .get_mut()
+            .send_failures
+            .add(Instant::now(), window)
+    }
+
+    pub fn clear_send_failures(&mut self) {
+        self.inner.get_mut().send_failures.clear()
+    }
+
     pub fn remove(self) {
         self.inner.remove();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::p2p::comm::OutboundSubscription;
+    use futures::Stream;
+    use poldercast::NodeProfileBuilder;
+    use slog::Logger;
+
+    fn node_id() -> Id {
+        (*NodeProfileBuilder::new().build().id()).into()
+    }
+
+    fn header_hash(n: usize) -> HeaderHash {
+        HeaderHash::hash_bytes(&n.to_le_bytes())
+    }
+
+    #[test]
+    fn known_headers_are_bounded() {
+        let mut known = KnownHeaders::default();
+        for n in 0..KNOWN_HEADERS_CAPACITY {
+            known.insert(header_hash(n));
+        }
+        // Inserting a known header again does not push out older ones.
+        known.insert(header_hash(0));
+        assert!(known.contains(&header_hash(0)));
+        assert!(!known.contains(&header_hash(KNOWN_HEADERS_CAPACITY)));
+
+        known.insert(header_hash(KNOWN_HEADERS_CAPACITY));
+        assert!(!known.contains(&header_hash(0)));
+        assert!(known.contains(&header_hash(1)));
+        assert!(known.contains(&header_hash(KNOWN_HEADERS_CAPACITY)));
+        assert_eq!(known.hashes.len(), KNOWN_HEADERS_CAPACITY);
+    }
+
+    #[test]
+    fn send_failures_are_counted_within_window() {
+        let window = Duration::from_secs(10);
+        let start = Instant::now();
+        let mut failures = SendFailures::default();
+        assert_eq!(failures.add(start, window), 1);
+        assert_eq!(failures.add(start + Duration::from_secs(5), window), 2);
+        // A failure after the window starts a new count.
+        assert_eq!(failures.add(start + Duration::from_secs(10), window), 1);
+        assert_eq!(failures.add(start + Duration::from_secs(11), window), 2);
+        failures.clear();
+        assert_eq!(failures.add(start + Duration::from_secs(12), window), 1);
+    }
+
+    // Adds a peer subscribed to block solicitations, with one item
+    // queued in the subscription stream.
+    fn add_subscribed_peer(map: &mut PeerMap, id: Id) -> OutboundSubscription<Vec<HeaderHash>> {
+        let mut comms = PeerComms::new();
+        let subscription = comms.subscribe_to_block_solicitations();
+        comms
+            .block_solicitations
+            .try_send(vec![header_hash(0)])
+            .unwrap();
+        assert!(map.insert_peer(id, comms));
+        subscription
+    }
+
+    #[test]
+    fn stuck_subscriber_is_evicted() {
+        let timeout = Duration::from_secs(60);
+        let start = Instant::now();
+        let mut map = peer_map(8, EvictionPolicy::Lru);
+        let active_ids = vec![node_id(), node_id()];
+        let stuck_id = node_id();
+        let mut active_subscriptions: Vec<_> = active_ids
+            .iter()
+            .map(|&id| add_subscribed_peer(&mut map, id))
+            .collect();
+        let _stuck_subscription = add_subscribed_peer(&mut map, stuck_id);
+
+        // None of the peers take their items, which is not blamed on them.
+        assert!(map.evict_stalled(timeout, start).is_empty());
+
+        for subscription in &mut active_subscriptions {
+            let item = subscription.wait().next().unwrap().unwrap();
+            assert_eq!(item, vec![header_hash(0)]);
+        }
+        // The stuck peer is not evicted before the timeout expires.
+        let now = start + timeout / 2;
+        assert!(map.evict_stalled(timeout, now).is_empty());
+
+        for (&id, subscription) in active_ids.iter().zip(&mut active_subscriptions) {
+            map.peer_comms(id)
+                .unwrap()
+                .block_solicitations
+                .try_send(vec![header_hash(1)])
+                .unwrap();
+            let item = subscription.wait().next().unwrap().unwrap();
+            assert_eq!(item, vec![header_hash(1)]);
+        }
+        let now = start + timeout;
+        assert_eq!(map.evict_stalled(timeout, now), vec![stuck_id]);
+        assert!(map.entry(stuck_id).is_none());
+        for &id in &active_ids {
+            assert!(map.entry(id).is_some());
+        }
+    }
+
+    fn peer_map(capacity: usize, eviction_policy: EvictionPolicy) -> PeerMap {
+        PeerMap::new(
+            capacity,
+            eviction_policy,
+            StatsCounter::default(),
+            EventBus::new(StatsCounter::default(), Logger::root(slog::Discard, o!())),
+        )
+    }
+
+    #[test]
+    fn lru_policy_evicts_least_recently_active_peer() {
+        let mut map = peer_map(2, EvictionPolicy::Lru);
+        let (first_id, second_id, new_id) = (node_id(), node_id(), node_id());
+        assert!(map.insert_peer(first_id, PeerComms::new()));
+        assert!(map.insert_peer(second_id, PeerComms::new()));
+        assert!(map.refresh_peer(first_id).is_some());
+
+        assert!(map.insert_peer(new_id, PeerComms::new()));
+        assert!(map.entry(second_id).is_none());
+        assert!(map.entry(first_id).is_some());
+        assert!(map.entry(new_id).is_some());
+
+        // Replacing an existing peer does not evict anyone.
+        assert!(map.insert_peer(first_id, PeerComms::new()));
+        assert!(map.entry(new_id).is_some());
+    }
+
+    #[test]
+    fn reject_new_policy_keeps_connected_peers() {
+        let mut map = peer_map(2, EvictionPolicy::RejectNew);
+        let (first_id, second_id, new_id) = (node_id(), node_id(), node_id());
+        assert!(map.insert_peer(first_id, PeerComms::new()));
+        assert!(map.insert_peer(second_id, PeerComms::new()));
+
+        assert!(!map.insert_peer(new_id, PeerComms::new()));
+        assert!(map.server_comms(new_id).is_none());
+        assert!(map.entry(new_id).is_none());
+        assert!(map.entry(first_id).is_some());
+        assert!(map.entry(second_id).is_some());
+
+        // A connected peer is still served.
+        assert!(map.server_comms(first_id).is_some());
+    }
+}
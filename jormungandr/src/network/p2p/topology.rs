@@ -2,16 +2,19 @@
 //!
 
 use crate::network::p2p::{Gossips, Id, Node};
+use crate::stats_counter::StatsCounter;
+use crate::utils::lock::PoisonRecovery;
 use poldercast::{
     poldercast::{Cyclon, Rings, Vicinity},
     Layer, NodeProfile, PolicyReport, StrikeReason, Topology,
 };
 use slog::Logger;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
 
 /// object holding the P2pTopology of the Node
 pub struct P2pTopology {
     lock: Arc<RwLock<Topology>>,
+    poison_recovery: PoisonRecovery,
     logger: Logger,
 }
 
@@ -19,17 +22,26 @@ impl P2pTopology {
     /// create a new P2pTopology for the given Address and Id
     ///
     /// The address is the public
-    pub fn new(node: poldercast::NodeProfile, logger: Logger) -> Self {
+    pub fn new(node: poldercast::NodeProfile, stats_counter: StatsCounter, logger: Logger) -> Self {
         P2pTopology {
             lock: Arc::new(RwLock::new(Topology::new(node))),
+            poison_recovery: PoisonRecovery::new("topology", stats_counter, logger.clone()),
             logger,
         }
     }
 
+    fn read(&self) -> RwLockReadGuard<Topology> {
+        self.poison_recovery.recover(self.lock.read())
+    }
+
+    fn write(&self) -> RwLockWriteGuard<Topology> {
+        self.poison_recovery.recover(self.lock.write())
+    }
+
     /// set a P2P Topology Module. Each module will work independently from
     /// each other and will help improve the node connectivity
     pub fn add_module<M: Layer + Send + Sync + 'static>(&self, module: M) {
-        let mut topology = self.lock.write().unwrap();
+        let mut topology = self.write();
         info!(
             self.logger,
             "adding P2P Topology module: {}",
@@ -40,7 +52,7 @@ impl P2pTopology {
 
     /// set all the default poldercast modules (Rings, Vicinity and Cyclon)
     pub fn set_poldercast_modules(&mut self) {
-        let mut topology = self.lock.write().unwrap();
+        let mut topology = self.write();
         topology.add_layer(Rings::default());
         topology.add_layer(Vicinity::default());
         topology.add_layer(Cyclon::default());
@@ -49,7 +61,7 @@ impl P2pTopology {
     /// Returns a list of neighbors selected in this turn
     /// to contact for event dissemination.
     pub fn view(&self) -> Vec<Node> {
-        let mut topology = self.lock.write().unwrap();
+        let mut topology = self.write();
         topology
             .view(None, poldercast::Selection::Any)
             .into_iter()
@@ -58,24 +70,24 @@ impl P2pTopology {
     }
 
     pub fn initiate_gossips(&self, with: Id) -> Gossips {
-        let mut topology = self.lock.write().unwrap();
+        let mut topology = self.write();
         topology.initiate_gossips(with.into()).into()
     }
 
     pub fn accept_gossips(&self, from: Id, gossips: Gossips) {
-        let mut topology = self.lock.write().unwrap();
+        let mut topology = self.write();
         topology.accept_gossips(from.into(), gossips.into())
     }
 
     pub fn exchange_gossips(&mut self, with: Id, gossips: Gossips) -> Gossips {
-        let mut topology = self.lock.write().unwrap();
+        let mut topology = self.write();
         topology
             .exchange_gossips(with.into(), gossips.into())
             .into()
     }
 
     pub fn node(&self) -> NodeProfile {
-        self.lock.read().unwrap().profile().clone()
+        self.read().profile().clone()
     }
 
     /// register a strike against the given node id
@@ -83,7 +95,7 @@ impl P2pTopology {
     /// the function returns `None` if the node was not even in the
     /// the topology (not even quarantined).
     pub fn report_node(&self, node: Id, issue: StrikeReason) -> Option<PolicyReport> {
-        let mut topology = self.lock.write().unwrap();
+        let mut topology = self.write();
         topology.update_node(node.into(), |node| {
             node.record_mut().strike(issue);
         })
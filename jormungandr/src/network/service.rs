@@ -1,5 +1,6 @@
 use super::{
     buffer_sizes,
+    events::{NetworkEvent, SubscriptionKind},
     inbound::InboundProcessing,
     p2p::comm::{BlockEventSubscription, OutboundSubscription},
     p2p::{Gossip as NodeData, Id},
@@ -45,6 +46,23 @@ where
     fn subscription_logger(&self, subscriber: <Self as P2pService>::NodeId) -> Logger {
         self.logger.new(o!("node_id" => subscriber.to_string()))
     }
+
+    fn notify_subscription(&self, node_id: Id, kind: SubscriptionKind) {
+        self.global_state
+            .events
+            .emit(NetworkEvent::SubscriptionAdded { node_id, kind });
+    }
+}
+
+fn subscription_rejected(logger: &Logger) -> core_error::Error {
+    info!(
+        logger,
+        "rejecting subscription: the limit on connections is reached"
+    );
+    core_error::Error::new(
+        core_error::Code::ResourceExhausted,
+        "the limit on connections is reached",
+    )
 }
 
 impl Node for NodeService {
@@ -195,7 +213,11 @@ impl BlockService for NodeService {
             logger.new(o!("direction" => "in")),
         );
 
-        let outbound = self.global_state.peers.serve_block_events(subscriber);
+        let outbound = match self.global_state.peers.serve_block_events(subscriber) {
+            Some(outbound) => outbound,
+            None => return future::err(subscription_rejected(&logger)),
+        };
+        self.notify_subscription(subscriber, SubscriptionKind::BlockEvents);
 
         let subscription = Subscription::new(sink, outbound, logger);
         future::ok(subscription)
@@ -229,7 +251,11 @@ impl FragmentService for NodeService {
             logger.new(o!("direction" => "in")),
         );
 
-        let outbound = self.global_state.peers.serve_fragments(subscriber);
+        let outbound = match self.global_state.peers.serve_fragments(subscriber) {
+            Some(outbound) => outbound,
+            None => return future::err(subscription_rejected(&logger)),
+        };
+        self.notify_subscription(subscriber, SubscriptionKind::Fragments);
 
         let subscription = Subscription::new(sink, outbound, logger);
         future::ok(subscription)
@@ -252,7 +278,11 @@ impl GossipService for NodeService {
             logger.new(o!("direction" => "in")),
         );
 
-        let outbound = self.global_state.peers.serve_gossip(subscriber);
+        let outbound = match self.global_state.peers.serve_gossip(subscriber) {
+            Some(outbound) => outbound,
+            None => return future::err(subscription_rejected(&logger)),
+        };
+        self.notify_subscription(subscriber, SubscriptionKind::Gossip);
 
         let subscription = Subscription::new(sink, outbound, logger);
         future::ok(subscription)
@@ -279,6 +279,7 @@ impl Sink for BlockAnnouncementProcessor {
     type SinkError = core_error::Error;
 
     fn start_send(&mut self, header: Header) -> StartSend<Header, core_error::Error> {
+        let hash = header.hash();
         let polled = self
             .mbox
             .start_send(BlockMsg::AnnouncedBlock(header, self.node_id))
@@ -292,7 +293,9 @@ impl Sink for BlockAnnouncementProcessor {
             })?;
         match polled {
             AsyncSink::Ready => {
-                self.global_state.peers.refresh_peer_on_block(self.node_id);
+                self.global_state
+                    .peers
+                    .refresh_peer_on_block(self.node_id, hash);
                 Ok(AsyncSink::Ready)
             }
             AsyncSink::NotReady(BlockMsg::AnnouncedBlock(header, _)) => {
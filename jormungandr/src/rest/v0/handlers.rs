@@ -15,6 +15,7 @@ use chain_impl_mockchain::value::{Value, ValueError};
 use crate::blockchain::Ref;
 use crate::intercom::{self, NetworkMsg, TransactionMsg};
 use crate::secure::NodeSecret;
+use crate::stats_counter::PeerDropReason;
 use bytes::{Bytes, IntoBuf};
 use futures::{
     future::{
@@ -161,6 +162,11 @@ pub fn get_stats_counter(context: State<Context>) -> ActixFuture!() {
                     Ok(Some(json!({
                         "txRecvCnt": stats.tx_recv_cnt(),
                         "blockRecvCnt": stats.block_recv_cnt(),
+                        "peerDropSendErrorCnt": stats.peer_drop_cnt(PeerDropReason::SendError),
+                        "peerDropIdleCnt": stats.peer_drop_cnt(PeerDropReason::Idle),
+                        "peerDropEvictedCnt": stats.peer_drop_cnt(PeerDropReason::Evicted),
+                        "peerDropPolicyCnt": stats.peer_drop_cnt(PeerDropReason::Policy),
+                        "lockPoisonedCnt": stats.lock_poisoned_cnt(),
                         "uptime": stats.uptime_sec(),
                         "lastBlockHash": tip_header.hash().to_string(),
                         "lastBlockHeight": tip_header.chain_length().to_string(),
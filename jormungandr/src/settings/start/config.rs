@@ -1,6 +1,7 @@
 use crate::{
     network::p2p::{topic, Id},
     settings::logging::{LogFormat, LogOutput},
+    settings::start::network::EvictionPolicy,
     settings::LOG_FILTER_LEVEL_POSSIBLE_VALUES,
 };
 use jormungandr_lib::time::Duration;
@@ -24,6 +25,9 @@ pub struct Config {
     #[serde(default)]
     pub leadership: Leadership,
 
+    #[serde(default)]
+    pub chain_selection: ChainSelection,
+
     pub rest: Option<Rest>,
 
     #[serde(default)]
@@ -88,6 +92,54 @@ pub struct P2pConfig {
     /// If not specified, an internal default limit is used.
     pub max_connections: Option<usize>,
 
+    /// What to do when a new peer connects while the limit
+    /// on connections is reached.
+    /// If not specified, the least recently active peer is evicted.
+    pub eviction_policy: Option<EvictionPolicy>,
+
+    /// Limit on the number of requests (block solicitations, chain pulls)
+    /// this node keeps in flight towards a single peer.
+    /// If not specified, an internal default limit is used.
+    pub max_inflight_requests_per_peer: Option<usize>,
+
+    /// Time after which a peer that does not take any items from its
+    /// subscriptions, while items are waiting to be sent to it,
+    /// is disconnected.
+    /// If not specified, an internal default is used.
+    pub subscription_stall_timeout: Option<Duration>,
+
+    /// Number of failures to send an item to a peer, each occurring within
+    /// `subscription_failure_window` of the first one, after which
+    /// the peer is disconnected. Isolated failures only drop the item.
+    /// If not specified, an internal default is used.
+    pub subscription_failure_limit: Option<usize>,
+
+    /// Time window in which the failures to send items to a peer are
+    /// counted towards `subscription_failure_limit`.
+    /// If not specified, an internal default is used.
+    pub subscription_failure_window: Option<Duration>,
+
+    /// Limit on the total time spent bootstrapping from a single peer.
+    /// If not specified, the bootstrap is not limited in time.
+    pub bootstrap_timeout: Option<Duration>,
+
+    /// Time after which the bootstrap from a peer is aborted if the peer
+    /// does not send any blocks.
+    /// If not specified, an internal default is used.
+    pub bootstrap_stall_timeout: Option<Duration>,
+
+    /// Interval between the bootstrap progress reports in the log.
+    /// If not specified, an internal default is used.
+    pub bootstrap_progress_interval: Option<Duration>,
+
+    /// Whether to disable Nagle's algorithm on the P2P TCP connections.
+    /// The default is to set TCP_NODELAY.
+    pub tcp_nodelay: Option<bool>,
+
+    /// Idle time after which TCP keepalive probes are sent on the
+    /// P2P connections. If not specified, keepalive is not enabled.
+    pub tcp_keepalive: Option<Duration>,
+
     /// Whether to allow non-public IP addresses on the network.
     /// The default is to not allow advertising non-public IP addresses.
     #[serde(default)]
@@ -111,6 +163,14 @@ pub struct Leadership {
     pub garbage_collection_interval: Duration,
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChainSelection {
+    /// maximum number of blocks to roll back from the current tip when
+    /// switching to another branch. Branches forking off deeper are ignored.
+    pub max_reorg_depth: Option<u32>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct Mempool {
@@ -163,6 +223,16 @@ impl Default for P2pConfig {
             trusted_peers: None,
             topics_of_interest: None,
             max_connections: None,
+            eviction_policy: None,
+            max_inflight_requests_per_peer: None,
+            subscription_stall_timeout: None,
+            subscription_failure_limit: None,
+            subscription_failure_window: None,
+            bootstrap_timeout: None,
+            bootstrap_stall_timeout: None,
+            bootstrap_progress_interval: None,
+            tcp_nodelay: None,
+            tcp_keepalive: None,
             allow_private_addresses: false,
         }
     }
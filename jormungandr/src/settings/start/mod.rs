@@ -1,7 +1,7 @@
 pub mod config;
 pub mod network;
 
-use self::config::{Config, Leadership, Mempool};
+use self::config::{ChainSelection, Config, Leadership, Mempool};
 pub use self::config::{Cors, Rest};
 use self::network::Protocol;
 use crate::rest::Error as RestError;
@@ -18,6 +18,7 @@ custom_error! {pub Error
    ExpectedBlock0Info = "Cannot start the node without the information to retrieve the genesis block",
    TooMuchBlock0Info = "Use only `--genesis-block-hash' or `--genesis-block'",
    ListenAddressNotValid = "In the node configuration file, the `p2p.listen_address` value is not a valid address. Use format `/ip4/x.x.x.x/tcp/4920",
+   MaxInflightRequestsZero = "In the node configuration file, the `p2p.max_inflight_requests_per_peer` value must be at least 1",
 }
 
 /// Overall Settings for node
@@ -29,6 +30,7 @@ pub struct Settings {
     pub rest: Option<Rest>,
     pub mempool: Mempool,
     pub leadership: Leadership,
+    pub chain_selection: ChainSelection,
     pub explorer: bool,
 }
 
@@ -110,7 +112,7 @@ impl RawSettings {
             config,
         } = self;
         let command_arguments = &command_line.start_arguments;
-        let network = generate_network(&command_arguments, &config)?;
+        let network = generate_network(&command_arguments, &config, logger)?;
 
         let storage = match (
             command_arguments.storage.as_ref(),
@@ -162,6 +164,9 @@ impl RawSettings {
             leadership: config
                 .as_ref()
                 .map_or(Leadership::default(), |cfg| cfg.leadership.clone()),
+            chain_selection: config
+                .as_ref()
+                .map_or(ChainSelection::default(), |cfg| cfg.chain_selection.clone()),
             explorer,
         })
     }
@@ -170,6 +175,7 @@ impl RawSettings {
 fn generate_network(
     command_arguments: &StartArguments,
     config: &Option<Config>,
+    logger: &Logger,
 ) -> Result<network::Configuration, Error> {
     let mut p2p = if let Some(cfg) = config {
         cfg.p2p.clone()
@@ -218,20 +224,136 @@ fn generate_network(
                 }
             }
         },
-        trusted_peers: p2p
-            .trusted_peers
-            .clone()
-            .unwrap_or(vec![])
-            .into_iter()
-            .map(Into::into)
-            .collect(),
+        trusted_peers: dedup_trusted_peers(
+            p2p.trusted_peers
+                .clone()
+                .unwrap_or(vec![])
+                .into_iter()
+                .map(Into::into),
+            logger,
+        ),
         protocol: Protocol::Grpc,
         max_connections: p2p
             .max_connections
             .unwrap_or(network::DEFAULT_MAX_CONNECTIONS),
+        eviction_policy: p2p.eviction_policy.unwrap_or_default(),
+        max_inflight_requests_per_peer: match p2p.max_inflight_requests_per_peer {
+            Some(0) => return Err(Error::MaxInflightRequestsZero),
+            Some(max) => max,
+            None => network::DEFAULT_MAX_INFLIGHT_REQUESTS_PER_PEER,
+        },
+        subscription_stall_timeout: p2p
+            .subscription_stall_timeout
+            .map(Into::into)
+            .unwrap_or(network::DEFAULT_SUBSCRIPTION_STALL_TIMEOUT),
+        subscription_failure_limit: p2p
+            .subscription_failure_limit
+            .unwrap_or(network::DEFAULT_SUBSCRIPTION_FAILURE_LIMIT),
+        subscription_failure_window: p2p
+            .subscription_failure_window
+            .map(Into::into)
+            .unwrap_or(network::DEFAULT_SUBSCRIPTION_FAILURE_WINDOW),
+        bootstrap_timeout: p2p.bootstrap_timeout.map(Into::into),
+        bootstrap_stall_timeout: p2p
+            .bootstrap_stall_timeout
+            .map(Into::into)
+            .unwrap_or(network::DEFAULT_BOOTSTRAP_STALL_TIMEOUT),
+        bootstrap_progress_interval: p2p
+            .bootstrap_progress_interval
+            .map(Into::into)
+            .unwrap_or(network::DEFAULT_BOOTSTRAP_PROGRESS_INTERVAL),
+        tcp: network::TcpOptions {
+            nodelay: p2p.tcp_nodelay.unwrap_or(true),
+            keepalive: p2p.tcp_keepalive.map(Into::into),
+        },
         timeout: std::time::Duration::from_secs(15),
         allow_private_addresses: p2p.allow_private_addresses,
     };
 
     Ok(network)
 }
+
+/// Removes the repeated entries of the same trusted peer address,
+/// which may come from both the configuration file and the command line,
+/// so that the node does not connect to the same peer more than once.
+/// If the entries disagree on the node ID, the first one is used.
+fn dedup_trusted_peers<I>(peers: I, logger: &Logger) -> Vec<network::TrustedPeer>
+where
+    I: IntoIterator<Item = network::TrustedPeer>,
+{
+    let mut deduped: Vec<network::TrustedPeer> = Vec::new();
+    for peer in peers {
+        match deduped.iter().find(|p| p.address == peer.address) {
+            None => deduped.push(peer),
+            Some(existing) if existing.id == peer.id => {
+                info!(
+                    logger,
+                    "ignoring duplicate trusted peer entry";
+                    "address" => ?peer.address,
+                );
+            }
+            Some(existing) => {
+                warn!(
+                    logger,
+                    "trusted peer address is listed with different node IDs, using the first one";
+                    "address" => ?peer.address,
+                    "id" => %existing.id,
+                    "ignored_id" => %peer.id,
+                );
+            }
+        }
+    }
+    deduped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::p2p::Id;
+    use poldercast::NodeProfileBuilder;
+
+    fn node_id() -> Id {
+        (*NodeProfileBuilder::new().build().id()).into()
+    }
+
+    fn trusted_peer(address: &str, id: Id) -> network::TrustedPeer {
+        network::TrustedPeer {
+            address: address.parse().unwrap(),
+            id,
+        }
+    }
+
+    #[test]
+    fn duplicate_trusted_peers_are_removed() {
+        let logger = Logger::root(slog::Discard, o!());
+        let (first_id, second_id, other_id) = (node_id(), node_id(), node_id());
+        let first = trusted_peer("/ip4/10.0.0.1/tcp/3000", first_id);
+        let second = trusted_peer("/ip4/10.0.0.2/tcp/3000", second_id);
+        let peers = vec![
+            first.clone(),
+            second.clone(),
+            // The same entry, e.g. given on the command line as well.
+            first.clone(),
+            // The same address with another node ID.
+            trusted_peer("/ip4/10.0.0.2/tcp/3000", other_id),
+        ];
+
+        let deduped = dedup_trusted_peers(peers, &logger);
+        assert_eq!(deduped, vec![first, second]);
+    }
+
+    #[test]
+    fn distinct_trusted_peers_are_kept() {
+        let logger = Logger::root(slog::Discard, o!());
+        let id = node_id();
+        // Different ports of the same host are different peers.
+        let peers = vec![
+            trusted_peer("/ip4/10.0.0.1/tcp/3000", id),
+            trusted_peer("/ip4/10.0.0.1/tcp/3001", id),
+        ];
+
+        let deduped = dedup_trusted_peers(peers.clone(), &logger);
+        assert_eq!(deduped, peers);
+        assert!(dedup_trusted_peers(vec![], &logger).is_empty());
+    }
+}
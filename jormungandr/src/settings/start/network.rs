@@ -9,6 +9,41 @@ pub enum Protocol {
     Grpc,
 }
 
+/// How to make room for a new peer when the limit on
+/// connections is reached.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EvictionPolicy {
+    /// Evict the peer that has been inactive for the longest time.
+    Lru,
+    /// Keep the connected peers and reject the new one.
+    RejectNew,
+}
+
+impl Default for EvictionPolicy {
+    fn default() -> Self {
+        EvictionPolicy::Lru
+    }
+}
+
+/// Socket options for the P2P TCP connections.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TcpOptions {
+    /// Disable Nagle's algorithm.
+    pub nodelay: bool,
+    /// Idle time before sending keepalive probes, if enabled.
+    pub keepalive: Option<Duration>,
+}
+
+impl Default for TcpOptions {
+    fn default() -> Self {
+        TcpOptions {
+            nodelay: true,
+            keepalive: None,
+        }
+    }
+}
+
 /// represent a connection peer
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Peer {
@@ -37,6 +72,31 @@ pub struct Listen {
 /// used unless the corresponding configuration option is specified.
 pub const DEFAULT_MAX_CONNECTIONS: usize = 256;
 
+/// The limit on the number of client requests in flight to a single peer
+/// used unless the corresponding configuration option is specified.
+pub const DEFAULT_MAX_INFLIGHT_REQUESTS_PER_PEER: usize = 4;
+
+/// The time a peer is allowed to not take any items from its subscriptions
+/// used unless the corresponding configuration option is specified.
+pub const DEFAULT_SUBSCRIPTION_STALL_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The number of failures to send items to a peer, within
+/// `DEFAULT_SUBSCRIPTION_FAILURE_WINDOW`, after which the peer is disconnected,
+/// used unless the corresponding configuration option is specified.
+pub const DEFAULT_SUBSCRIPTION_FAILURE_LIMIT: usize = 3;
+
+/// The time window in which the failures to send items to a peer are counted
+/// used unless the corresponding configuration option is specified.
+pub const DEFAULT_SUBSCRIPTION_FAILURE_WINDOW: Duration = Duration::from_secs(10);
+
+/// The time the bootstrap peer is allowed to not send any blocks
+/// used unless the corresponding configuration option is specified.
+pub const DEFAULT_BOOTSTRAP_STALL_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The interval between the bootstrap progress reports in the log
+/// used unless the corresponding configuration option is specified.
+pub const DEFAULT_BOOTSTRAP_PROGRESS_INTERVAL: Duration = Duration::from_secs(30);
+
 const DEFAULT_TIMEOUT_MICROSECONDS: u64 = 500_000;
 
 ///
@@ -59,6 +119,38 @@ pub struct Configuration {
     /// Maximum allowed number of peer connections.
     pub max_connections: usize,
 
+    /// What to do when the limit on peer connections is reached.
+    pub eviction_policy: EvictionPolicy,
+
+    /// Maximum allowed number of requests in flight to a single peer.
+    pub max_inflight_requests_per_peer: usize,
+
+    /// Time after which a peer not receiving items from its subscriptions
+    /// is disconnected.
+    pub subscription_stall_timeout: Duration,
+
+    /// Number of failures to send items to a peer within
+    /// `subscription_failure_window` after which the peer is disconnected.
+    pub subscription_failure_limit: usize,
+
+    /// Time window in which the failures to send items to a peer
+    /// are counted.
+    pub subscription_failure_window: Duration,
+
+    /// Limit on the time spent bootstrapping from a single peer,
+    /// if any.
+    pub bootstrap_timeout: Option<Duration>,
+
+    /// Time after which the bootstrap from a peer that does not send
+    /// any blocks is aborted.
+    pub bootstrap_stall_timeout: Duration,
+
+    /// Interval between the bootstrap progress reports in the log.
+    pub bootstrap_progress_interval: Duration,
+
+    /// Socket options for the TCP connections with peers.
+    pub tcp: TcpOptions,
+
     /// the default value for the timeout for inactive connection
     pub timeout: Duration,
 
@@ -66,12 +158,72 @@ pub struct Configuration {
     pub allow_private_addresses: bool,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct TrustedPeer {
     pub address: poldercast::Address,
     pub id: Id,
 }
 
+/// A setting that has a different value in the newer configuration.
+#[allow(dead_code)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Changed<T> {
+    pub old: T,
+    pub new: T,
+}
+
+/// The differences between two network configurations.
+///
+/// This is the information needed to apply a new configuration
+/// to the running node without restarting it. The node profile is
+/// not compared, as the public ID and the topics of interest of
+/// a running node cannot change; the public address only matters
+/// through the listener.
+///
+/// Nothing reloads the configuration yet, so the diff is unused
+/// outside of tests.
+#[allow(dead_code)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConfigDiff {
+    /// trusted peers present only in the newer configuration
+    pub added_trusted_peers: Vec<TrustedPeer>,
+    /// trusted peers present only in the older configuration
+    pub removed_trusted_peers: Vec<TrustedPeer>,
+    /// the listener to start, if it is added or changed
+    pub added_listen: Option<Listen>,
+    /// the listener to stop, if it is removed or changed
+    pub removed_listen: Option<Listen>,
+    pub max_connections: Option<Changed<usize>>,
+    pub eviction_policy: Option<Changed<EvictionPolicy>>,
+    pub max_inflight_requests_per_peer: Option<Changed<usize>>,
+    pub timeout: Option<Changed<Duration>>,
+    pub subscription_stall_timeout: Option<Changed<Duration>>,
+    pub subscription_failure_limit: Option<Changed<usize>>,
+    pub subscription_failure_window: Option<Changed<Duration>>,
+    pub bootstrap_timeout: Option<Changed<Option<Duration>>>,
+    pub bootstrap_stall_timeout: Option<Changed<Duration>>,
+    pub bootstrap_progress_interval: Option<Changed<Duration>>,
+    pub tcp: Option<Changed<TcpOptions>>,
+    pub allow_private_addresses: Option<Changed<bool>>,
+}
+
+#[allow(dead_code)]
+impl ConfigDiff {
+    /// Returns true if there are no differences.
+    pub fn is_empty(&self) -> bool {
+        *self == ConfigDiff::default()
+    }
+}
+
+#[allow(dead_code)]
+fn changed<T: PartialEq>(old: T, new: T) -> Option<Changed<T>> {
+    if old == new {
+        None
+    } else {
+        Some(Changed { old, new })
+    }
+}
+
 impl From<super::config::TrustedPeer> for TrustedPeer {
     fn from(tp: super::config::TrustedPeer) -> Self {
         TrustedPeer {
@@ -123,4 +275,244 @@ impl Configuration {
                 .and_then(|address| address.to_socketaddr()))
             .map(|addr| Listen::new(addr, self.protocol))
     }
+
+    /// Checks if the node can neither accept connections from peers
+    /// nor connect to any, and so runs in isolation.
+    pub fn is_isolated(&self) -> bool {
+        self.listen().is_none() && self.trusted_peers.is_empty()
+    }
+
+    /// Compares this configuration with a newer one and returns
+    /// the differences.
+    #[allow(dead_code)]
+    pub fn diff(&self, new: &Configuration) -> ConfigDiff {
+        let added_trusted_peers = new
+            .trusted_peers
+            .iter()
+            .filter(|peer| !self.trusted_peers.contains(peer))
+            .cloned()
+            .collect();
+        let removed_trusted_peers = self
+            .trusted_peers
+            .iter()
+            .filter(|peer| !new.trusted_peers.contains(peer))
+            .cloned()
+            .collect();
+
+        let (added_listen, removed_listen) = match (new.listen(), self.listen()) {
+            (new_listen, old_listen) if new_listen == old_listen => (None, None),
+            (new_listen, old_listen) => (new_listen, old_listen),
+        };
+
+        ConfigDiff {
+            added_trusted_peers,
+            removed_trusted_peers,
+            added_listen,
+            removed_listen,
+            max_connections: changed(self.max_connections, new.max_connections),
+            eviction_policy: changed(self.eviction_policy, new.eviction_policy),
+            max_inflight_requests_per_peer: changed(
+                self.max_inflight_requests_per_peer,
+                new.max_inflight_requests_per_peer,
+            ),
+            timeout: changed(self.timeout, new.timeout),
+            subscription_stall_timeout: changed(
+                self.subscription_stall_timeout,
+                new.subscription_stall_timeout,
+            ),
+            subscription_failure_limit: changed(
+                self.subscription_failure_limit,
+                new.subscription_failure_limit,
+            ),
+            subscription_failure_window: changed(
+                self.subscription_failure_window,
+                new.subscription_failure_window,
+            ),
+            bootstrap_timeout: changed(self.bootstrap_timeout, new.bootstrap_timeout),
+            bootstrap_stall_timeout: changed(
+                self.bootstrap_stall_timeout,
+                new.bootstrap_stall_timeout,
+            ),
+            bootstrap_progress_interval: changed(
+                self.bootstrap_progress_interval,
+                new.bootstrap_progress_interval,
+            ),
+            tcp: changed(self.tcp, new.tcp),
+            allow_private_addresses: changed(
+                self.allow_private_addresses,
+                new.allow_private_addresses,
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use poldercast::NodeProfileBuilder;
+
+    fn trusted_peer(address: &str) -> TrustedPeer {
+        TrustedPeer {
+            address: address.parse().unwrap(),
+            id: (*NodeProfileBuilder::new().build().id()).into(),
+        }
+    }
+
+    fn configuration() -> Configuration {
+        Configuration {
+            listen_address: Some("127.0.0.1:8299".parse().unwrap()),
+            profile: NodeProfileBuilder::new().build(),
+            trusted_peers: vec![
+                trusted_peer("/ip4/10.0.0.1/tcp/3000"),
+                trusted_peer("/ip4/10.0.0.2/tcp/3000"),
+            ],
+            protocol: Protocol::Grpc,
+            max_connections: DEFAULT_MAX_CONNECTIONS,
+            eviction_policy: EvictionPolicy::Lru,
+            max_inflight_requests_per_peer: DEFAULT_MAX_INFLIGHT_REQUESTS_PER_PEER,
+            subscription_stall_timeout: DEFAULT_SUBSCRIPTION_STALL_TIMEOUT,
+            subscription_failure_limit: DEFAULT_SUBSCRIPTION_FAILURE_LIMIT,
+            subscription_failure_window: DEFAULT_SUBSCRIPTION_FAILURE_WINDOW,
+            bootstrap_timeout: None,
+            bootstrap_stall_timeout: DEFAULT_BOOTSTRAP_STALL_TIMEOUT,
+            bootstrap_progress_interval: DEFAULT_BOOTSTRAP_PROGRESS_INTERVAL,
+            tcp: TcpOptions::default(),
+            timeout: Duration::from_micros(DEFAULT_TIMEOUT_MICROSECONDS),
+            allow_private_addresses: false,
+        }
+    }
+
+    #[test]
+    fn same_configuration_has_empty_diff() {
+        let config = configuration();
+        assert!(config.diff(&config.clone()).is_empty());
+    }
+
+    #[test]
+    fn diff_trusted_peers() {
+        let old = configuration();
+        let mut new = old.clone();
+        let removed = new.trusted_peers.remove(0);
+        let added = trusted_peer("/ip4/10.0.0.3/tcp/3000");
+        new.trusted_peers.push(added.clone());
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.added_trusted_peers, vec![added]);
+        assert_eq!(diff.removed_trusted_peers, vec![removed]);
+        assert_eq!(
+            diff,
+            ConfigDiff {
+                added_trusted_peers: diff.added_trusted_peers.clone(),
+                removed_trusted_peers: diff.removed_trusted_peers.clone(),
+                ..ConfigDiff::default()
+            }
+        );
+    }
+
+    #[test]
+    fn diff_listen() {
+        let old = configuration();
+        let mut new = old.clone();
+        new.listen_address = Some("127.0.0.1:8300".parse().unwrap());
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.added_listen, new.listen());
+        assert_eq!(diff.removed_listen, old.listen());
+
+        new.listen_address = None;
+        let diff = old.diff(&new);
+        assert_eq!(diff.added_listen, None);
+        assert_eq!(diff.removed_listen, old.listen());
+
+        let diff = new.diff(&old);
+        assert_eq!(diff.added_listen, old.listen());
+        assert_eq!(diff.removed_listen, None);
+    }
+
+    #[test]
+    fn diff_changed_settings() {
+        let old = configuration();
+        let mut new = old.clone();
+        new.max_connections = 16;
+        new.eviction_policy = EvictionPolicy::RejectNew;
+        new.max_inflight_requests_per_peer = 1;
+        new.timeout = Duration::from_secs(1);
+        new.subscription_stall_timeout = Duration::from_secs(5);
+        new.subscription_failure_limit = 1;
+        new.subscription_failure_window = Duration::from_secs(1);
+        new.bootstrap_timeout = Some(Duration::from_secs(600));
+        new.bootstrap_stall_timeout = Duration::from_secs(5);
+        new.bootstrap_progress_interval = Duration::from_secs(5);
+        new.tcp = TcpOptions {
+            nodelay: false,
+            keepalive: Some(Duration::from_secs(30)),
+        };
+        new.allow_private_addresses = true;
+
+        let diff = old.diff(&new);
+        assert_eq!(
+            diff,
+            ConfigDiff {
+                max_connections: changed(old.max_connections, 16),
+                eviction_policy: changed(EvictionPolicy::Lru, EvictionPolicy::RejectNew),
+                max_inflight_requests_per_peer: changed(old.max_inflight_requests_per_peer, 1),
+                timeout: changed(old.timeout, new.timeout),
+                subscription_stall_timeout: changed(
+                    old.subscription_stall_timeout,
+                    new.subscription_stall_timeout
+                ),
+                subscription_failure_limit: changed(old.subscription_failure_limit, 1),
+                subscription_failure_window: changed(
+                    old.subscription_failure_window,
+                    new.subscription_failure_window
+                ),
+                bootstrap_timeout: changed(None, new.bootstrap_timeout),
+                bootstrap_stall_timeout: changed(
+                    old.bootstrap_stall_timeout,
+                    new.bootstrap_stall_timeout
+                ),
+                bootstrap_progress_interval: changed(
+                    old.bootstrap_progress_interval,
+                    new.bootstrap_progress_interval
+                ),
+                tcp: changed(old.tcp, new.tcp),
+                allow_private_addresses: changed(false, true),
+                ..ConfigDiff::default()
+            }
+        );
+        assert!(diff.max_connections.is_some());
+        assert!(diff.allow_private_addresses.is_some());
+    }
+    #[test]
+    fn isolated_without_listen_address_and_trusted_peers() {
+        let mut config = configuration();
+        config.listen_address = None;
+        config.trusted_peers.clear();
+        assert!(config.is_isolated());
+    }
+
+    #[test]
+    fn not_isolated_with_listen_address() {
+        let mut config = configuration();
+        config.trusted_peers.clear();
+        assert!(!config.is_isolated());
+    }
+
+    #[test]
+    fn not_isolated_with_trusted_peers() {
+        let mut config = configuration();
+        config.listen_address = None;
+        assert!(!config.is_isolated());
+    }
+
+    #[test]
+    fn not_isolated_with_public_address() {
+        let mut config = configuration();
+        config.listen_address = None;
+        config.trusted_peers.clear();
+        let mut profile = NodeProfileBuilder::new();
+        profile.address("/ip4/127.0.0.1/tcp/8299".parse().unwrap());
+        config.profile = profile.build();
+        assert!(!config.is_isolated());
+    }
 }
@@ -88,10 +88,11 @@ pub fn load_blockchain(
     storage: NodeStorage,
     epoch_event: mpsc::Sender<NewEpochToSchedule>,
     block_cache_ttl: Duration,
+    max_reorg_depth: Option<u32>,
 ) -> Result<(Blockchain, Tip), Error> {
     use tokio::prelude::*;
 
-    let mut blockchain = Blockchain::new(storage, block_cache_ttl);
+    let mut blockchain = Blockchain::new(storage, block_cache_ttl, max_reorg_depth);
     let mut blockchain_clone = blockchain.clone();
 
     let main_branch: Branch = match blockchain.load_from_block0(block0.clone()).wait() {
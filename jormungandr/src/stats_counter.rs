@@ -5,6 +5,19 @@ use std::time::Instant;
 
 const SLOT_START_TIME_UNDEFINED: u64 = u64::max_value();
 
+/// The reason for removing a peer from the connected peers.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PeerDropReason {
+    /// Sending an item or a request to the peer failed.
+    SendError,
+    /// The peer stopped taking items from its subscriptions.
+    Idle,
+    /// The peer was evicted to make room for a new peer.
+    Evicted,
+    /// A subscription from a new peer was refused by the eviction policy.
+    Policy,
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct StatsCounter {
     stats: Arc<StatsCounterImpl>,
@@ -14,6 +27,8 @@ pub struct StatsCounter {
 struct StatsCounterImpl {
     tx_recv_cnt: AtomicUsize,
     block_recv_cnt: AtomicUsize,
+    peer_drop_cnt: [AtomicUsize; 4],
+    lock_poisoned_cnt: AtomicUsize,
     start_time: Instant,
     slot_start_time: AtomicU64,
 }
@@ -23,6 +38,8 @@ impl Default for StatsCounterImpl {
         Self {
             tx_recv_cnt: AtomicUsize::default(),
             block_recv_cnt: AtomicUsize::default(),
+            peer_drop_cnt: Default::default(),
+            lock_poisoned_cnt: AtomicUsize::default(),
             start_time: Instant::now(),
             slot_start_time: AtomicU64::new(SLOT_START_TIME_UNDEFINED),
         }
@@ -49,6 +66,24 @@ impl StatsCounter {
         self.stats.block_recv_cnt.load(Ordering::Relaxed) as u64
     }
 
+    pub fn add_peer_drop_cnt(&self, reason: PeerDropReason, count: usize) {
+        self.stats.peer_drop_cnt[reason as usize].fetch_add(count, Ordering::Relaxed);
+    }
+
+    pub fn peer_drop_cnt(&self, reason: PeerDropReason) -> u64 {
+        self.stats.peer_drop_cnt[reason as usize].load(Ordering::Relaxed) as u64
+    }
+
+    pub fn add_lock_poisoned_cnt(&self, count: usize) {
+        self.stats
+            .lock_poisoned_cnt
+            .fetch_add(count, Ordering::Relaxed);
+    }
+
+    pub fn lock_poisoned_cnt(&self) -> u64 {
+        self.stats.lock_poisoned_cnt.load(Ordering::Relaxed) as u64
+    }
+
     pub fn uptime_sec(&self) -> u64 {
         self.stats.start_time.elapsed().as_secs()
     }
@@ -0,0 +1,80 @@
+//! Recovery from poisoned locks.
+//!
+//! A lock is poisoned when a thread panics while holding it. For the shared
+//! network state, which stays usable between individual updates, it is better
+//! to keep going with the data than to have every later user of the lock
+//! panic in turn.
+
+use crate::stats_counter::StatsCounter;
+use slog::Logger;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LockResult};
+
+#[derive(Clone)]
+pub struct PoisonRecovery {
+    name: &'static str,
+    reported: Arc<AtomicBool>,
+    stats_counter: StatsCounter,
+    logger: Logger,
+}
+
+impl PoisonRecovery {
+    pub fn new(name: &'static str, stats_counter: StatsCounter, logger: Logger) -> Self {
+        PoisonRecovery {
+            name,
+            reported: Arc::new(AtomicBool::new(false)),
+            stats_counter,
+            logger,
+        }
+    }
+
+    /// Returns the guard of an acquired lock, even if the lock is poisoned.
+    ///
+    /// The first time the lock is found poisoned, a warning is logged and
+    /// the poisoned lock counter is incremented.
+    pub fn recover<G>(&self, result: LockResult<G>) -> G {
+        result.unwrap_or_else(|poisoned| {
+            if !self.reported.swap(true, Ordering::Relaxed) {
+                warn!(
+                    self.logger,
+                    "lock poisoned by a panicked thread, recovering";
+                    "lock" => self.name,
+                );
+                self.stats_counter.add_lock_poisoned_cnt(1);
+            }
+            poisoned.into_inner()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use std::thread;
+
+    #[test]
+    fn recovers_poisoned_lock() {
+        let stats_counter = StatsCounter::default();
+        let logger = Logger::root(slog::Discard, o!());
+        let recovery = PoisonRecovery::new("test", stats_counter.clone(), logger);
+        let mutex = Arc::new(Mutex::new(vec![1]));
+
+        let res = {
+            let mutex = mutex.clone();
+            thread::spawn(move || {
+                let _guard = mutex.lock().unwrap();
+                panic!("poisoning the lock");
+            })
+            .join()
+        };
+        assert!(res.is_err());
+        assert!(mutex.is_poisoned());
+
+        recovery.recover(mutex.lock()).push(2);
+        recovery.recover(mutex.lock()).push(3);
+        assert_eq!(*recovery.recover(mutex.lock()), vec![1, 2, 3]);
+        assert_eq!(stats_counter.lock_poisoned_cnt(), 1);
+    }
+}
@@ -1,4 +1,5 @@
 pub mod async_msg;
 pub mod borrow;
 pub mod drop_watchdog;
+pub mod lock;
 pub mod task;